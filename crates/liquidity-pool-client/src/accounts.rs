@@ -0,0 +1,60 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use liquidity_pool::state::{Pool, RewardVault, User};
+
+use crate::error::ClientError;
+
+/// decodes a zero-copy `Pool` account. baseline borsh pools are rejected until migrate_legacy_pool moves them.
+pub fn decode_pool(data: &[u8]) -> Result<Pool, ClientError> {
+    if !data.starts_with(Pool::DISCRIMINATOR) {
+        return Err(ClientError::InvalidDiscriminator("Pool"));
+    }
+    let body = data
+        .get(8..8 + std::mem::size_of::<Pool>())
+        .ok_or(ClientError::InvalidLength("Pool"))?;
+    Ok(bytemuck::pod_read_unaligned(body))
+}
+
+pub fn decode_user(data: &[u8]) -> Result<User, ClientError> {
+    if !data.starts_with(User::DISCRIMINATOR) {
+        return Err(ClientError::InvalidDiscriminator("User"));
+    }
+    User::try_deserialize(&mut &data[..]).map_err(|_| ClientError::InvalidLength("User"))
+}
+
+pub fn decode_reward_vault(data: &[u8]) -> Result<RewardVault, ClientError> {
+    if !data.starts_with(RewardVault::DISCRIMINATOR) {
+        return Err(ClientError::InvalidDiscriminator("RewardVault"));
+    }
+    RewardVault::try_deserialize(&mut &data[..]).map_err(|_| ClientError::InvalidLength("RewardVault"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn decodes_what_the_program_writes() {
+        let mut pool = Pool::zeroed();
+        pool.total_usdc_deposit = 5_000;
+        pool.fee_tier = 30;
+
+        let mut data = Pool::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&pool));
+        let decoded = decode_pool(&data).unwrap();
+        assert_eq!((decoded.total_usdc_deposit, decoded.fee_tier), (5_000, 30));
+
+        let user = User { usdc_deposit: 1, sol_deposit: 2, total_shares: 3, version: 1, ..Default::default() };
+        let mut data = Vec::new();
+        user.try_serialize(&mut data).unwrap();
+        assert_eq!(decode_user(&data).unwrap().total_shares, 3);
+    }
+
+    #[test]
+    fn rejects_short_or_foreign_accounts() {
+        assert!(matches!(decode_pool(&[0; 216]), Err(ClientError::InvalidDiscriminator(_))));
+        assert!(matches!(decode_pool(Pool::DISCRIMINATOR), Err(ClientError::InvalidLength(_))));
+        assert!(matches!(decode_user(Pool::DISCRIMINATOR), Err(ClientError::InvalidDiscriminator(_))));
+    }
+}
@@ -0,0 +1,343 @@
+//! aggregator adapter, shaped after the `Amm` trait of `jupiter-amm-interface` so an
+//! integration is a thin wrapper around `LiquidityPoolAmm`. the types are our own to keep
+//! the client free of aggregator dependencies.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+};
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::AccountMeta};
+use solana_account::Account;
+
+use crate::{
+    accounts::decode_pool,
+    error::ClientError,
+    instructions::{self, PoolKeys},
+    pda::pool_address,
+    quote::quote_swap,
+    Pool,
+};
+
+pub type AccountMap = HashMap<Pubkey, Account>;
+
+/// unix time shared between the aggregator's clock updater and its amms
+#[derive(Clone, Debug, Default)]
+pub struct ClockRef {
+    pub unix_timestamp: Arc<AtomicI64>,
+}
+
+impl ClockRef {
+    pub fn update(&self, unix_timestamp: i64) {
+        self.unix_timestamp.store(unix_timestamp, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AmmContext {
+    pub clock_ref: ClockRef,
+}
+
+/// the pool account doesn't store its mints, so they come along as params and are
+/// checked against the pool address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolParams {
+    pub usdc_mint: Pubkey,
+    pub wrapped_sol_mint: Pubkey,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyedAccount {
+    pub key: Pubkey,
+    pub account: Account,
+    pub params: PoolParams,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SwapMode {
+    #[default]
+    ExactIn,
+    ExactOut,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct QuoteParams {
+    pub amount: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub swap_mode: SwapMode,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quote {
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub fee_amount: u64,
+    pub fee_mint: Pubkey,
+    pub fee_bps: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SwapParams {
+    pub swap_mode: SwapMode,
+    pub in_amount: u64,
+    pub source_mint: Pubkey,
+    pub destination_mint: Pubkey,
+    pub source_token_account: Pubkey,
+    pub destination_token_account: Pubkey,
+    pub token_transfer_authority: Pubkey,
+    pub user: Pubkey,
+}
+
+/// instruction data and accounts of the swap, the program id is `Amm::program_id`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapAndAccountMetas {
+    pub data: Vec<u8>,
+    pub account_metas: Vec<AccountMeta>,
+}
+
+pub trait Amm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self, ClientError>
+    where
+        Self: Sized;
+    fn label(&self) -> String;
+    fn program_id(&self) -> Pubkey;
+    /// the pool address
+    fn key(&self) -> Pubkey;
+    fn get_reserve_mints(&self) -> Vec<Pubkey>;
+    /// accounts `update` reads
+    fn get_accounts_to_update(&self) -> Vec<Pubkey>;
+    fn update(&mut self, account_map: &AccountMap) -> Result<(), ClientError>;
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote, ClientError>;
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas, ClientError>;
+    fn supports_exact_out(&self) -> bool {
+        false
+    }
+    fn get_accounts_len(&self) -> usize;
+}
+
+#[derive(Clone)]
+pub struct LiquidityPoolAmm {
+    keys: PoolKeys,
+    pool: Pool,
+    clock_ref: ClockRef,
+}
+
+impl LiquidityPoolAmm {
+    /// (usdc in, curve amount, now) for an exact-in swap of `amount` of `input_mint`.
+    /// usdc swaps pay the fee on top of the curve amount, so `amount` is split the same
+    /// way swap_route splits what arrived.
+    fn curve_amount(&self, amount: u64, input_mint: &Pubkey, output_mint: &Pubkey) -> Result<(bool, u64, i64), ClientError> {
+        let usdc_in = if *input_mint == self.keys.usdc_mint && *output_mint == self.keys.wrapped_sol_mint {
+            true
+        } else if *input_mint == self.keys.wrapped_sol_mint && *output_mint == self.keys.usdc_mint {
+            false
+        } else {
+            let unknown = if *input_mint == self.keys.usdc_mint || *input_mint == self.keys.wrapped_sol_mint { output_mint } else { input_mint };
+            return Err(ClientError::UnknownMint(*unknown));
+        };
+
+        let now = self.clock_ref.unix_timestamp.load(Ordering::Relaxed);
+        let mut pool = self.pool;
+        let fee_bps = pool.swap_fee_bps(now)?;
+        let curve_amount = if usdc_in { ((amount as u128) * 10_000 / (10_000 + fee_bps as u128)) as u64 } else { amount };
+        Ok((usdc_in, curve_amount, now))
+    }
+}
+
+impl Amm for LiquidityPoolAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self, ClientError> {
+        let pool = decode_pool(&keyed_account.account.data)?;
+        let params = keyed_account.params;
+        if pool_address(&params.usdc_mint, &params.wrapped_sol_mint, pool.fee_tier).0 != keyed_account.key {
+            return Err(ClientError::PoolMismatch);
+        }
+
+        Ok(LiquidityPoolAmm {
+            keys: PoolKeys::new(params.usdc_mint, params.wrapped_sol_mint, pool.fee_tier),
+            pool,
+            clock_ref: amm_context.clock_ref.clone(),
+        })
+    }
+
+    fn label(&self) -> String {
+        "Liquidity Pool".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        liquidity_pool::ID
+    }
+
+    fn key(&self) -> Pubkey {
+        self.keys.pool()
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.keys.usdc_mint, self.keys.wrapped_sol_mint]
+    }
+
+    /// the mints are read for their owners, token-2022 mints need the other token program
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.keys.pool(), self.keys.usdc_mint, self.keys.wrapped_sol_mint]
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<(), ClientError> {
+        let get = |key: &Pubkey| account_map.get(key).ok_or(ClientError::MissingAccount(*key));
+
+        self.pool = decode_pool(&get(&self.keys.pool())?.data)?;
+        self.keys.usdc_token_program = get(&self.keys.usdc_mint)?.owner;
+        self.keys.sol_token_program = get(&self.keys.wrapped_sol_mint)?.owner;
+        Ok(())
+    }
+
+    /// token-2022 transfer fees are not taken into account
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote, ClientError> {
+        if quote_params.swap_mode == SwapMode::ExactOut {
+            return Err(ClientError::UnsupportedSwap("exact out"));
+        }
+        let (usdc_in, curve_amount, now) = self.curve_amount(quote_params.amount, &quote_params.input_mint, &quote_params.output_mint)?;
+        let quote = quote_swap(&self.pool, curve_amount, usdc_in, now)?;
+
+        Ok(Quote {
+            in_amount: quote.amount_in,
+            out_amount: quote.amount_out,
+            fee_amount: quote.fee,
+            fee_mint: self.keys.usdc_mint,
+            fee_bps: quote.fee_bps,
+        })
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas, ClientError> {
+        if swap_params.swap_mode == SwapMode::ExactOut {
+            return Err(ClientError::UnsupportedSwap("exact out"));
+        }
+        if swap_params.token_transfer_authority != swap_params.user {
+            return Err(ClientError::UnsupportedSwap("the user has to own the source account"));
+        }
+        let (usdc_in, curve_amount, _) = self.curve_amount(swap_params.in_amount, &swap_params.source_mint, &swap_params.destination_mint)?;
+
+        let destination = if usdc_in { self.keys.user_sol_ata(&swap_params.user) } else { self.keys.user_usdc_ata(&swap_params.user) };
+        if swap_params.destination_token_account != destination {
+            return Err(ClientError::UnsupportedSwap("output only goes to the user's ata"));
+        }
+
+        // the router enforces its own slippage bound on the output
+        let ix = instructions::swap_from(&self.keys, &swap_params.user, &swap_params.source_token_account, curve_amount, 0, usdc_in, false);
+        Ok(SwapAndAccountMetas { data: ix.data, account_metas: ix.accounts })
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        14
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    use anchor_spl::token::spl_token;
+    use bytemuck::Zeroable;
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account { lamports: 1_000_000, data, owner, executable: false, rent_epoch: 0 }
+    }
+
+    fn pool_account(pool: &Pool) -> Account {
+        let mut data = Pool::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(pool));
+        account(liquidity_pool::ID, data)
+    }
+
+    /// a pool snapshot with 5m usdc / 100k sol at 30 bps, plus its keyed account
+    fn snapshot() -> (Pool, KeyedAccount) {
+        let params = PoolParams { usdc_mint: Pubkey::new_unique(), wrapped_sol_mint: Pubkey::new_unique() };
+        let mut pool = Pool::zeroed();
+        pool.fee_tier = 30;
+        pool.liquidity_fees = 30;
+        pool.add_liquidity(5_000_000_000_000, 100_000_000_000_000).unwrap();
+
+        let key = pool_address(&params.usdc_mint, &params.wrapped_sol_mint, 30).0;
+        (pool, KeyedAccount { key, account: pool_account(&pool), params })
+    }
+
+    #[test]
+    fn quotes_exact_in_including_the_fee_on_top() {
+        let (pool, keyed) = snapshot();
+        let amm = LiquidityPoolAmm::from_keyed_account(&keyed, &AmmContext::default()).unwrap();
+        let params = keyed.params;
+        assert_eq!(amm.get_reserve_mints(), vec![params.usdc_mint, params.wrapped_sol_mint]);
+
+        let quote = amm
+            .quote(&QuoteParams { amount: 1_003_000_000, input_mint: params.usdc_mint, output_mint: params.wrapped_sol_mint, swap_mode: SwapMode::ExactIn })
+            .unwrap();
+        let expected = quote_swap(&pool, 1_000_000_000, true, 0).unwrap();
+        assert_eq!((quote.in_amount, quote.out_amount, quote.fee_amount), (1_003_000_000, expected.amount_out, 3_000_000));
+        assert_eq!(quote.fee_mint, params.usdc_mint);
+
+        let quote = amm
+            .quote(&QuoteParams { amount: 1_000_000_000, input_mint: params.wrapped_sol_mint, output_mint: params.usdc_mint, swap_mode: SwapMode::ExactIn })
+            .unwrap();
+        assert_eq!(quote.out_amount, quote_swap(&pool, 1_000_000_000, false, 0).unwrap().amount_out);
+
+        let foreign = Pubkey::new_unique();
+        let bad = QuoteParams { amount: 1, input_mint: foreign, output_mint: params.usdc_mint, swap_mode: SwapMode::ExactIn };
+        assert!(matches!(amm.quote(&bad), Err(ClientError::UnknownMint(mint)) if mint == foreign));
+        let exact_out = QuoteParams { swap_mode: SwapMode::ExactOut, ..bad };
+        assert!(matches!(amm.quote(&exact_out), Err(ClientError::UnsupportedSwap(_))));
+    }
+
+    #[test]
+    fn update_picks_up_new_reserves_and_token_programs() {
+        let (mut pool, keyed) = snapshot();
+        let mut amm = LiquidityPoolAmm::from_keyed_account(&keyed, &AmmContext::default()).unwrap();
+        assert!(matches!(amm.update(&AccountMap::new()), Err(ClientError::MissingAccount(_))));
+
+        pool.swap_usdc_for_sol(1_000_000_000, 30).unwrap();
+        let token_2022 = anchor_spl::token_2022::ID;
+        let accounts = AccountMap::from([
+            (keyed.key, pool_account(&pool)),
+            (keyed.params.usdc_mint, account(token_2022, vec![])),
+            (keyed.params.wrapped_sol_mint, account(spl_token::ID, vec![])),
+        ]);
+        amm.update(&accounts).unwrap();
+        assert_eq!(amm.pool.total_usdc_deposit, pool.total_usdc_deposit);
+        assert_eq!((amm.keys.usdc_token_program, amm.keys.sol_token_program), (token_2022, spl_token::ID));
+    }
+
+    #[test]
+    fn rejects_pools_that_do_not_match_their_params() {
+        let (_, mut keyed) = snapshot();
+        keyed.params.wrapped_sol_mint = Pubkey::new_unique();
+        assert!(matches!(LiquidityPoolAmm::from_keyed_account(&keyed, &AmmContext::default()), Err(ClientError::PoolMismatch)));
+    }
+
+    #[test]
+    fn swap_metas_match_the_instruction_builder() {
+        let (_, keyed) = snapshot();
+        let amm = LiquidityPoolAmm::from_keyed_account(&keyed, &AmmContext::default()).unwrap();
+        let keys = PoolKeys::new(keyed.params.usdc_mint, keyed.params.wrapped_sol_mint, 30);
+        let user = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+
+        let mut params = SwapParams {
+            swap_mode: SwapMode::ExactIn,
+            in_amount: 1_003_000_000,
+            source_mint: keys.usdc_mint,
+            destination_mint: keys.wrapped_sol_mint,
+            source_token_account: source,
+            destination_token_account: keys.user_sol_ata(&user),
+            token_transfer_authority: user,
+            user,
+        };
+        let swap = amm.get_swap_and_account_metas(&params).unwrap();
+        let ix = instructions::swap_from(&keys, &user, &source, 1_000_000_000, 0, true, false);
+        assert_eq!(swap, SwapAndAccountMetas { data: ix.data, account_metas: ix.accounts });
+        assert_eq!(swap.account_metas.len(), amm.get_accounts_len());
+
+        params.destination_token_account = Pubkey::new_unique();
+        assert!(matches!(amm.get_swap_and_account_metas(&params), Err(ClientError::UnsupportedSwap(_))));
+    }
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::Pubkey;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("account is not a {0}")]
+    InvalidDiscriminator(&'static str),
+    #[error("{0} account data too short, migrate it first")]
+    InvalidLength(&'static str),
+    #[error("account {0} missing from the account map")]
+    MissingAccount(Pubkey),
+    #[error("pool address does not match its mints and fee tier")]
+    PoolMismatch,
+    #[error("mint {0} is not traded by this pool")]
+    UnknownMint(Pubkey),
+    #[error("unsupported swap: {0}")]
+    UnsupportedSwap(&'static str),
+    #[error("program rejected the quote: {0}")]
+    Program(#[from] anchor_lang::error::Error),
+}
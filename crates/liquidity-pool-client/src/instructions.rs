@@ -0,0 +1,424 @@
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token, token::spl_token};
+
+use crate::pda::{config_address, lp_address, pool_address, position_address, program_data_address, reward_vault_address, vault_address};
+
+/// everything that identifies a pool, the rest of its accounts derive from these
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub usdc_mint: Pubkey,
+    pub wrapped_sol_mint: Pubkey,
+    pub fee_tier: u16,
+    pub usdc_token_program: Pubkey,
+    pub sol_token_program: Pubkey,
+    /// mirrors `Pool::rewards_enabled`, deposits and withdrawals then pass the reward vault
+    pub rewards_enabled: bool,
+}
+
+impl PoolKeys {
+    /// pool on two classic spl token mints
+    pub fn new(usdc_mint: Pubkey, wrapped_sol_mint: Pubkey, fee_tier: u16) -> Self {
+        PoolKeys {
+            usdc_mint,
+            wrapped_sol_mint,
+            fee_tier,
+            usdc_token_program: spl_token::ID,
+            sol_token_program: spl_token::ID,
+            rewards_enabled: false,
+        }
+    }
+
+    pub fn pool(&self) -> Pubkey {
+        pool_address(&self.usdc_mint, &self.wrapped_sol_mint, self.fee_tier).0
+    }
+
+    pub fn pool_usdc_ata(&self) -> Pubkey {
+        vault_address(&self.pool(), &self.usdc_mint, &self.usdc_token_program)
+    }
+
+    pub fn pool_sol_ata(&self) -> Pubkey {
+        vault_address(&self.pool(), &self.wrapped_sol_mint, &self.sol_token_program)
+    }
+
+    pub fn reward_vault(&self) -> Pubkey {
+        reward_vault_address(&self.pool()).0
+    }
+
+    pub fn user_usdc_ata(&self, owner: &Pubkey) -> Pubkey {
+        vault_address(owner, &self.usdc_mint, &self.usdc_token_program)
+    }
+
+    pub fn user_sol_ata(&self, owner: &Pubkey) -> Pubkey {
+        vault_address(owner, &self.wrapped_sol_mint, &self.sol_token_program)
+    }
+
+    pub fn lp(&self, owner: &Pubkey) -> Pubkey {
+        lp_address(&self.pool(), owner).0
+    }
+
+    pub fn position(&self, position_mint: &Pubkey) -> Pubkey {
+        position_address(&self.pool(), position_mint).0
+    }
+}
+
+/// the holder's ata for a position nft, position mints are always classic spl token
+pub fn position_token_account(holder: &Pubkey, position_mint: &Pubkey) -> Pubkey {
+    vault_address(holder, position_mint, &spl_token::ID)
+}
+
+/// `admin` has to be the program's upgrade authority
+pub fn initialize_config(admin: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::InitializeConfig {
+            admin: *admin,
+            config: config_address().0,
+            program_data: program_data_address().0,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::InitializeConfig {}.data(),
+    }
+}
+
+/// the first deposit into a pool also creates it
+pub fn deposit(keys: &PoolKeys, owner: &Pubkey, usdc_amount: u64, wrapped_sol_amount: u64, native_sol: bool) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::Deposit {
+            signer: *owner,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            user_usdc_ata: keys.user_usdc_ata(owner),
+            user_sol_ata: keys.user_sol_ata(owner),
+            user_pda: keys.lp(owner),
+            pool_pda: keys.pool(),
+            config: config_address().0,
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            associated_token_program: associated_token::ID,
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            system_program: system_program::ID,
+            reward_vault: keys.rewards_enabled.then(|| keys.reward_vault()),
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::Deposit {
+            usdc_amount,
+            wrapped_sol_amount,
+            fee_tier: keys.fee_tier,
+            native_sol,
+        }
+        .data(),
+    }
+}
+
+/// `usdc_in` swaps `swap_amount` usdc for sol, otherwise `swap_amount` sol for usdc.
+/// fails if the pool pays out less than `min_amount_out`
+pub fn swap(keys: &PoolKeys, owner: &Pubkey, swap_amount: u64, min_amount_out: u64, usdc_in: bool, native_sol: bool) -> Instruction {
+    let source = if usdc_in { keys.user_usdc_ata(owner) } else { keys.user_sol_ata(owner) };
+    swap_from(keys, owner, &source, swap_amount, min_amount_out, usdc_in, native_sol)
+}
+
+/// same as `swap` but pays from any token account of `owner`, the output still goes to
+/// the owner's ata since the program creates it if needed
+pub fn swap_from(keys: &PoolKeys, owner: &Pubkey, source: &Pubkey, swap_amount: u64, min_amount_out: u64, usdc_in: bool, native_sol: bool) -> Instruction {
+    let (user_base_ata, base_mint, base_token_program) = if usdc_in {
+        (keys.user_sol_ata(owner), keys.wrapped_sol_mint, keys.sol_token_program)
+    } else {
+        (keys.user_usdc_ata(owner), keys.usdc_mint, keys.usdc_token_program)
+    };
+
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::Swap {
+            signer: *owner,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            user_quote_ata: *source,
+            user_base_ata,
+            base_mint,
+            pool_pda: keys.pool(),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            base_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::Swap { swap_amount, min_amount_out, native_sol }.data(),
+    }
+}
+
+/// burns the owner's whole position and closes it
+pub fn withdraw(keys: &PoolKeys, owner: &Pubkey, native_sol: bool) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::WithDraw {
+            signer: *owner,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            user_pda: keys.lp(owner),
+            user_usdc_ata: keys.user_usdc_ata(owner),
+            user_sol_ata: keys.user_sol_ata(owner),
+            pool_pda: keys.pool(),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_wrapped_sol_ata: keys.pool_sol_ata(),
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            reward_vault: keys.rewards_enabled.then(|| keys.reward_vault()),
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::Withdraw { native_sol }.data(),
+    }
+}
+
+/// pays the owner the fees their position earned, the shares stay in the pool
+pub fn collect_fees(keys: &PoolKeys, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::CollectFees {
+            signer: *owner,
+            user_pda: keys.lp(owner),
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            pool_pda: keys.pool(),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            user_usdc_ata: keys.user_usdc_ata(owner),
+            user_sol_ata: keys.user_sol_ata(owner),
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::CollectFees {}.data(),
+    }
+}
+
+/// folds the pool's fee buckets into its reserves. the keeper reward goes to the given
+/// token accounts, a side without one is compounded in full.
+pub fn compound_fees(keys: &PoolKeys, keeper: &Pubkey, keeper_usdc_account: Option<Pubkey>, keeper_sol_account: Option<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::CompoundFees {
+            keeper: *keeper,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            pool_pda: keys.pool(),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            keeper_usdc_account,
+            keeper_sol_account,
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::CompoundFees {}.data(),
+    }
+}
+
+pub fn set_compound_reward(keys: &PoolKeys, authority: &Pubkey, reward_bps: u64) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::SetCompoundReward { authority: *authority, pool_pda: keys.pool() }.to_account_metas(None),
+        data: liquidity_pool::instruction::SetCompoundReward { reward_bps }.data(),
+    }
+}
+
+/// locks the owner's whole position for `duration` seconds for a reward boost
+pub fn lock_position(keys: &PoolKeys, owner: &Pubkey, duration: i64) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::LockPosition {
+            signer: *owner,
+            user_pda: keys.lp(owner),
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            pool_pda: keys.pool(),
+            reward_vault: keys.rewards_enabled.then(|| keys.reward_vault()),
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::LockPosition { duration }.data(),
+    }
+}
+
+/// drops the boost of the owner's ended lock, anyone can send it
+pub fn expire_lock(keys: &PoolKeys, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::ExpireLock {
+            user_pda: keys.lp(owner),
+            pool_pda: keys.pool(),
+            reward_vault: keys.rewards_enabled.then(|| keys.reward_vault()),
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::ExpireLock {}.data(),
+    }
+}
+
+/// creates the pool's reward vault, the authority's ata funds the whole schedule
+pub fn initialize_rewards(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_token_program: &Pubkey,
+    emission_rate: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Instruction {
+    let reward_vault = keys.reward_vault();
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::InitializeRewards {
+            authority: *authority,
+            pool_pda: keys.pool(),
+            reward_mint: *reward_mint,
+            reward_vault,
+            reward_vault_ata: vault_address(&reward_vault, reward_mint, reward_token_program),
+            authority_reward_ata: vault_address(authority, reward_mint, reward_token_program),
+            reward_token_program: *reward_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::InitializeRewards { emission_rate, start_time, end_time }.data(),
+    }
+}
+
+/// pays out the owner's rewards, has to run before a withdraw once any are owed
+pub fn claim_rewards(keys: &PoolKeys, owner: &Pubkey, reward_mint: &Pubkey, reward_token_program: &Pubkey) -> Instruction {
+    let reward_vault = keys.reward_vault();
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::ClaimRewards {
+            signer: *owner,
+            user_pda: keys.lp(owner),
+            pool_pda: keys.pool(),
+            reward_vault,
+            reward_mint: *reward_mint,
+            reward_vault_ata: vault_address(&reward_vault, reward_mint, reward_token_program),
+            user_reward_ata: vault_address(owner, reward_mint, reward_token_program),
+            reward_token_program: *reward_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::ClaimRewards {}.data(),
+    }
+}
+
+/// deposits as a new nft position. `position_mint` is a fresh keypair that has to sign too.
+pub fn open_position(keys: &PoolKeys, owner: &Pubkey, position_mint: &Pubkey, usdc_amount: u64, wrapped_sol_amount: u64) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::OpenPosition {
+            signer: *owner,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            user_usdc_ata: keys.user_usdc_ata(owner),
+            user_sol_ata: keys.user_sol_ata(owner),
+            pool_pda: keys.pool(),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            position_mint: *position_mint,
+            position_pda: keys.position(position_mint),
+            position_token_account: position_token_account(owner, position_mint),
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            position_token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            reward_vault: keys.rewards_enabled.then(|| keys.reward_vault()),
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::OpenPosition { usdc_amount, wrapped_sol_amount }.data(),
+    }
+}
+
+/// withdraws a whole nft position to `holder`, the position token has to sit in their ata
+pub fn close_position(keys: &PoolKeys, holder: &Pubkey, position_mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::ClosePosition {
+            signer: *holder,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            pool_pda: keys.pool(),
+            position_mint: *position_mint,
+            position_pda: keys.position(position_mint),
+            position_token_account: position_token_account(holder, position_mint),
+            user_usdc_ata: keys.user_usdc_ata(holder),
+            user_sol_ata: keys.user_sol_ata(holder),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            position_token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            reward_vault: keys.rewards_enabled.then(|| keys.reward_vault()),
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::ClosePosition {}.data(),
+    }
+}
+
+pub fn collect_position_fees(keys: &PoolKeys, holder: &Pubkey, position_mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::CollectPositionFees {
+            signer: *holder,
+            usdc_mint: keys.usdc_mint,
+            wrapped_sol_mint: keys.wrapped_sol_mint,
+            pool_pda: keys.pool(),
+            position_mint: *position_mint,
+            position_pda: keys.position(position_mint),
+            position_token_account: position_token_account(holder, position_mint),
+            pool_usdc_ata: keys.pool_usdc_ata(),
+            pool_sol_ata: keys.pool_sol_ata(),
+            user_usdc_ata: keys.user_usdc_ata(holder),
+            user_sol_ata: keys.user_sol_ata(holder),
+            usdc_token_program: keys.usdc_token_program,
+            sol_token_program: keys.sol_token_program,
+            position_token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::CollectPositionFees {}.data(),
+    }
+}
+
+pub fn claim_position_rewards(keys: &PoolKeys, holder: &Pubkey, position_mint: &Pubkey, reward_mint: &Pubkey, reward_token_program: &Pubkey) -> Instruction {
+    let reward_vault = keys.reward_vault();
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::ClaimPositionRewards {
+            signer: *holder,
+            pool_pda: keys.pool(),
+            position_mint: *position_mint,
+            position_pda: keys.position(position_mint),
+            position_token_account: position_token_account(holder, position_mint),
+            reward_vault,
+            reward_mint: *reward_mint,
+            reward_vault_ata: vault_address(&reward_vault, reward_mint, reward_token_program),
+            user_reward_ata: vault_address(holder, reward_mint, reward_token_program),
+            position_token_program: spl_token::ID,
+            reward_token_program: *reward_token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::ClaimPositionRewards {}.data(),
+    }
+}
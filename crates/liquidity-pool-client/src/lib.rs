@@ -0,0 +1,18 @@
+//! off-chain helpers for the liquidity pool program: pda derivation, instruction
+//! builders, account decoders, quotes that run the program's own pool math and an
+//! adapter for aggregators.
+
+pub mod accounts;
+pub mod amm;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+pub use accounts::{decode_pool, decode_reward_vault, decode_user};
+pub use error::ClientError;
+pub use instructions::PoolKeys;
+pub use liquidity_pool::{state::{lock_boost_bps, Pool, RewardVault, User}, ID};
+/// the pool math the program itself runs, for simulators that track reserves on their own
+pub use liquidity_pool_math as math;
+pub use quote::{quote_deposit, quote_fees, quote_rewards, quote_swap, quote_withdraw, SwapQuote, WithdrawQuote};
@@ -0,0 +1,40 @@
+use anchor_lang::{prelude::Pubkey, solana_program::bpf_loader_upgradeable};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+/// `[b"pool", usdc_mint, wrapped_sol_mint, fee_tier le bytes]`
+pub fn pool_address(usdc_mint: &Pubkey, wrapped_sol_mint: &Pubkey, fee_tier: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), fee_tier.to_le_bytes().as_ref()],
+        &liquidity_pool::ID,
+    )
+}
+
+/// `[b"lp", pool, owner]`, one position per owner in each pool
+pub fn lp_address(pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"lp", pool.as_ref(), owner.as_ref()], &liquidity_pool::ID)
+}
+
+/// `[b"position", pool, position_mint]`, an nft position owned by whoever holds the mint's token
+pub fn position_address(pool: &Pubkey, position_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"position", pool.as_ref(), position_mint.as_ref()], &liquidity_pool::ID)
+}
+
+/// `[b"rewards", pool]`, the pool's liquidity mining vault
+pub fn reward_vault_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"rewards", pool.as_ref()], &liquidity_pool::ID)
+}
+
+/// `[b"config"]`
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &liquidity_pool::ID)
+}
+
+/// the pool's vault for `mint`, an ata owned by the pool pda
+pub fn vault_address(pool: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(pool, mint, token_program)
+}
+
+/// `[program id]` under the upgradeable loader, records the program's upgrade authority
+pub fn program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[liquidity_pool::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
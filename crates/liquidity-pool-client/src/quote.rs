@@ -0,0 +1,164 @@
+//! quotes run the program's own `Pool` methods, backed by `liquidity-pool-math`, on a copy of
+//! the account, so they match what the instruction would do at the same state. token-2022
+//! transfer fees are not included.
+
+use liquidity_pool::state::{Pool, RewardVault, User};
+
+use crate::error::ClientError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// total the user sends, usdc swaps pay the fee on top of the swap amount
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// always charged in usdc
+    pub fee: u64,
+    pub fee_bps: u64,
+}
+
+impl SwapQuote {
+    /// `min_amount_out` for the swap instruction, `slippage_bps` below the quoted output
+    pub fn min_amount_out(&self, slippage_bps: u64) -> u64 {
+        let slippage_bps = slippage_bps.min(10_000);
+        ((self.amount_out as u128) * (10_000 - slippage_bps) as u128 / 10_000) as u64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    pub usdc: u64,
+    pub sol: u64,
+    pub usdc_fees: u64,
+    pub sol_fees: u64,
+}
+
+/// swap of `swap_amount` at unix time `now`, `now` matters when the pool uses dynamic fees
+pub fn quote_swap(pool: &Pool, swap_amount: u64, usdc_in: bool, now: i64) -> Result<SwapQuote, ClientError> {
+    let mut pool = *pool;
+    let fee_bps = pool.swap_fee_bps(now)?;
+
+    let quote = if usdc_in {
+        let (sol_out, fee) = pool.swap_usdc_for_sol(swap_amount, fee_bps)?;
+        SwapQuote { amount_in: swap_amount.saturating_add(fee), amount_out: sol_out, fee, fee_bps }
+    } else {
+        let (usdc_out, fee) = pool.swap_sol_for_usdc(swap_amount, fee_bps)?;
+        SwapQuote { amount_in: swap_amount, amount_out: usdc_out, fee, fee_bps }
+    };
+
+    Ok(quote)
+}
+
+/// shares minted for a deposit of `usdc_amount` and `wrapped_sol_amount`
+pub fn quote_deposit(pool: &Pool, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<u64, ClientError> {
+    let mut pool = *pool;
+    Ok(pool.add_liquidity(usdc_amount, wrapped_sol_amount)?)
+}
+
+/// what closing `user`'s position pays out, including its uncollected fees
+pub fn quote_withdraw(pool: &Pool, user: &User) -> Result<WithdrawQuote, ClientError> {
+    let (mut pool, mut user) = (*pool, user.clone());
+    let (usdc_fees, sol_fees) = user.collect_fees(&mut pool)?;
+    let (usdc, sol) = pool.remove_liquidity(user.total_shares)?;
+    Ok(WithdrawQuote { usdc, sol, usdc_fees, sol_fees })
+}
+
+/// (usdc, sol) fees `user` could collect without withdrawing
+pub fn quote_fees(pool: &Pool, user: &User) -> Result<(u64, u64), ClientError> {
+    let (mut pool, mut user) = (*pool, user.clone());
+    Ok(user.collect_fees(&mut pool)?)
+}
+
+/// rewards `user` could claim at unix time `now`, settled and unsettled
+pub fn quote_rewards(pool: &Pool, reward_vault: &RewardVault, user: &User, now: i64) -> Result<u64, ClientError> {
+    let (mut reward_vault, mut user) = (reward_vault.clone(), user.clone());
+    reward_vault.accrue(pool.reward_weight()?, now)?;
+    user.settle_rewards(&reward_vault)?;
+    Ok(user.rewards_owed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn pool() -> Pool {
+        let mut pool = Pool::zeroed();
+        pool.liquidity_fees = 30;
+        pool.add_liquidity(5_000_000_000_000, 100_000_000_000_000).unwrap();
+        pool
+    }
+
+    #[test]
+    fn quotes_leave_the_pool_untouched_and_match_the_program() {
+        let pool = pool();
+
+        let quote = quote_swap(&pool, 500_000_000, true, 0).unwrap();
+        let mut copy = pool;
+        let (sol_out, fee) = copy.swap_usdc_for_sol(500_000_000, 30).unwrap();
+        assert_eq!(quote, SwapQuote { amount_in: 500_000_000 + fee, amount_out: sol_out, fee, fee_bps: 30 });
+
+        let quote = quote_swap(&pool, 1_000_000_000, false, 0).unwrap();
+        assert_eq!(quote.amount_in, 1_000_000_000);
+        assert!(quote.amount_out > 0);
+
+        assert_eq!(pool.total_usdc_deposit, 5_000_000_000_000);
+        assert_eq!(pool.fees_collected_usdc, 0);
+    }
+
+    #[test]
+    fn min_amount_out_takes_slippage_off_the_output() {
+        let quote = SwapQuote { amount_in: 1_003, amount_out: 20_000, fee: 3, fee_bps: 30 };
+        assert_eq!(quote.min_amount_out(0), 20_000);
+        assert_eq!(quote.min_amount_out(50), 19_900);
+        assert_eq!(quote.min_amount_out(20_000), 0);
+    }
+
+    #[test]
+    fn deposit_and_withdraw_quotes_round_trip() {
+        let pool = pool();
+        let shares = quote_deposit(&pool, 50_000_000, 1_000_000_000).unwrap();
+
+        let lp = User { total_shares: pool.total_shares, ..Default::default() };
+        let withdraw = quote_withdraw(&pool, &lp).unwrap();
+        assert_eq!((withdraw.usdc, withdraw.sol), (pool.total_usdc_deposit, pool.total_sol_deposit));
+        assert!(shares > 0);
+        assert!(quote_deposit(&pool, 50_000_000, 0).is_err());
+    }
+
+    #[test]
+    fn fee_quotes_match_the_position_share() {
+        let mut pool = pool();
+        let lp = User { total_shares: pool.total_shares / 2, ..Default::default() };
+        pool.add_fees(1_000_000, 0).unwrap();
+
+        let (usdc_fees, sol_fees) = quote_fees(&pool, &lp).unwrap();
+        assert!(usdc_fees.abs_diff(500_000) <= 1 && sol_fees == 0);
+        assert_eq!(quote_withdraw(&pool, &lp).unwrap().usdc_fees, usdc_fees);
+        assert_eq!(pool.fees_collected_usdc, 1_000_000);
+    }
+
+    #[test]
+    fn reward_quotes_share_with_locked_positions() {
+        let mut pool = Pool::zeroed();
+        pool.total_shares = 200;
+        pool.boosted_shares = 100;
+        let reward_vault = RewardVault {
+            pool: Default::default(),
+            reward_mint: Default::default(),
+            emission_rate: 10,
+            start_time: 100,
+            end_time: 200,
+            last_update_time: 100,
+            reward_per_share: 0,
+            bump: 0,
+        };
+        // alice's lock doubles the position's weight, bob has no boost
+        let alice = User { total_shares: 100, locked_until: 1_000, lock_boost_bps: 10_000, boost_shares: 100, ..Default::default() };
+        let bob = User { total_shares: 100, ..Default::default() };
+
+        let alice_rewards = quote_rewards(&pool, &reward_vault, &alice, 200).unwrap();
+        let bob_rewards = quote_rewards(&pool, &reward_vault, &bob, 200).unwrap();
+        assert_eq!((alice_rewards, bob_rewards), (666, 333));
+        assert!(alice_rewards + bob_rewards <= reward_vault.total_emissions().unwrap());
+    }
+}
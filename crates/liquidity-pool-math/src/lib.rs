@@ -0,0 +1,190 @@
+//! constant product math for the liquidity pool, with no solana dependencies. the program
+//! and every off-chain quoter call these same functions, so quotes match on-chain results
+//! bit for bit. all functions are pure, callers apply the returned reserves themselves.
+
+#![cfg_attr(not(test), no_std)]
+
+/// fixed point scale for usdc per sol ratios
+pub const PRICE_SCALE: u128 = 100_000;
+/// denominator of a basis point
+pub const BPS_SCALE: u64 = 10_000;
+/// fixed point scale of per share accumulators, q64.64
+pub const GROWTH_SCALE: u128 = 1 << 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+    ImbalancedDeposit,
+    ZeroShares,
+    InsufficientLiquidity,
+}
+
+impl core::fmt::Display for MathError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let msg = match self {
+            MathError::Overflow => "arithmetic overflow",
+            MathError::Underflow => "arithmetic underflow",
+            MathError::DivisionByZero => "division by zero",
+            MathError::ImbalancedDeposit => "deposit does not match the pool ratio",
+            MathError::ZeroShares => "zero shares",
+            MathError::InsufficientLiquidity => "insufficient liquidity",
+        };
+        f.write_str(msg)
+    }
+}
+
+/// reserves after a swap and what the swapper gets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapOutcome {
+    pub amount_out: u64,
+    /// always in usdc
+    pub fee: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+/// usdc -> sol, `usdc_amount` goes into the curve and the fee is paid on top of it
+pub fn swap_usdc_for_sol(usdc_reserve: u64, sol_reserve: u64, usdc_amount: u64, fee_bps: u64) -> Result<SwapOutcome, MathError> {
+    let total_usdc = usdc_reserve.checked_add(usdc_amount).ok_or(MathError::Overflow)?;
+    let constant_product = sol_reserve as u128 * usdc_reserve as u128;
+    // the remaining reserve rounds up so rounding can never shrink k
+    let remaining_sol = ceil_div(constant_product, total_usdc as u128)? as u64;
+    let sol_out = sol_reserve.checked_sub(remaining_sol).ok_or(MathError::Underflow)?;
+    let fee = bps_of(usdc_amount, fee_bps)?;
+
+    Ok(SwapOutcome { amount_out: sol_out, fee, usdc_reserve: total_usdc, sol_reserve: remaining_sol })
+}
+
+/// sol -> usdc, the fee is taken out of the usdc leaving the curve
+pub fn swap_sol_for_usdc(usdc_reserve: u64, sol_reserve: u64, sol_amount: u64, fee_bps: u64) -> Result<SwapOutcome, MathError> {
+    let total_sol = sol_reserve.checked_add(sol_amount).ok_or(MathError::Overflow)?;
+    let constant_product = sol_reserve as u128 * usdc_reserve as u128;
+    let remaining_usdc = ceil_div(constant_product, total_sol as u128)? as u64;
+    let required_usdc = usdc_reserve.checked_sub(remaining_usdc).ok_or(MathError::Underflow)?;
+    let fee = bps_of(required_usdc, fee_bps)?;
+    let usdc_out = required_usdc.checked_sub(fee).ok_or(MathError::Underflow)?;
+
+    Ok(SwapOutcome { amount_out: usdc_out, fee, usdc_reserve: remaining_usdc, sol_reserve: total_sol })
+}
+
+/// shares minted for a deposit. an empty pool mints sqrt(usdc * sol), otherwise the deposit
+/// has to match the pool ratio within 1% and gets the smaller of the two proportional counts.
+pub fn deposit_shares(usdc_reserve: u64, sol_reserve: u64, total_shares: u64, usdc_amount: u64, sol_amount: u64) -> Result<u64, MathError> {
+    let shares = if total_shares == 0 {
+        (usdc_amount as u128 * sol_amount as u128).isqrt() as u64
+    } else {
+        let deposit_ratio = ratio(usdc_amount, sol_amount)?;
+        let pool_ratio = ratio(usdc_reserve, sol_reserve)?;
+        if deposit_ratio.abs_diff(pool_ratio) > pool_ratio / 100 {
+            return Err(MathError::ImbalancedDeposit);
+        }
+
+        let usdc_shares = checked_div(usdc_amount as u128 * total_shares as u128, usdc_reserve as u128)?;
+        let sol_shares = checked_div(sol_amount as u128 * total_shares as u128, sol_reserve as u128)?;
+        usdc_shares.min(sol_shares) as u64
+    };
+
+    if shares == 0 {
+        return Err(MathError::ZeroShares);
+    }
+    Ok(shares)
+}
+
+/// the part of `amount` owned by `shares` out of `total_shares`, rounded down
+pub fn pro_rata(amount: u64, shares: u64, total_shares: u64) -> Result<u64, MathError> {
+    if shares == 0 {
+        return Err(MathError::ZeroShares);
+    }
+    if shares > total_shares {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    Ok((amount as u128 * shares as u128 / total_shares as u128) as u64)
+}
+
+/// `amount * bps / 10_000`, rounded down
+pub fn bps_of(amount: u64, bps: u64) -> Result<u64, MathError> {
+    let value = (amount as u128).checked_mul(bps as u128).ok_or(MathError::Overflow)? / BPS_SCALE as u128;
+    Ok(value as u64)
+}
+
+/// what `amount` adds to a per share accumulator spread over `total_shares`, scaled by
+/// `GROWTH_SCALE`. nothing accrues while there are no shares.
+pub fn growth_per_share(amount: u128, total_shares: u64) -> Result<u128, MathError> {
+    if total_shares == 0 {
+        return Ok(0);
+    }
+    Ok(amount.checked_mul(GROWTH_SCALE).ok_or(MathError::Overflow)? / total_shares as u128)
+}
+
+/// what `shares` earned while an accumulator moved from `checkpoint` to `growth`, rounded down
+pub fn accrued(shares: u64, growth: u128, checkpoint: u128) -> Result<u64, MathError> {
+    let delta = growth.checked_sub(checkpoint).ok_or(MathError::Underflow)?;
+    let amount = delta.checked_mul(shares as u128).ok_or(MathError::Overflow)? / GROWTH_SCALE;
+    u64::try_from(amount).map_err(|_| MathError::Overflow)
+}
+
+pub fn ceil_div(numerator: u128, denominator: u128) -> Result<u128, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    Ok(numerator.div_ceil(denominator))
+}
+
+/// usdc per sol scaled by `PRICE_SCALE`
+fn ratio(usdc: u64, sol: u64) -> Result<u128, MathError> {
+    checked_div(usdc as u128 * PRICE_SCALE, sol as u128)
+}
+
+fn checked_div(numerator: u128, denominator: u128) -> Result<u128, MathError> {
+    numerator.checked_div(denominator).ok_or(MathError::DivisionByZero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swaps_round_the_remaining_reserve_up() {
+        let outcome = swap_usdc_for_sol(1_000, 1_000, 3, 30).unwrap();
+        // 1_000_000 / 1_003 = 997.00.. rounds up to 998
+        assert_eq!(outcome, SwapOutcome { amount_out: 2, fee: 0, usdc_reserve: 1_003, sol_reserve: 998 });
+
+        let outcome = swap_sol_for_usdc(5_000_000, 1_000_000, 10_000, 30).unwrap();
+        assert_eq!(outcome.usdc_reserve, 4_950_496);
+        assert_eq!(outcome.amount_out + outcome.fee, 5_000_000 - 4_950_496);
+        assert_eq!(outcome.fee, 148);
+        assert_eq!(swap_sol_for_usdc(5_000_000, u64::MAX, 1, 30), Err(MathError::Overflow));
+        assert_eq!(swap_usdc_for_sol(0, 0, 0, 30), Err(MathError::DivisionByZero));
+    }
+
+    #[test]
+    fn deposits_mint_sqrt_then_proportional_shares() {
+        assert_eq!(deposit_shares(0, 0, 0, 4_000, 1_000), Ok(2_000));
+        assert_eq!(deposit_shares(4_000, 1_000, 2_000, 400, 100), Ok(200));
+        // the smaller side wins within the 1% band
+        assert_eq!(deposit_shares(4_000, 1_000, 2_000, 400, 101), Ok(200));
+        assert_eq!(deposit_shares(4_000, 1_000, 2_000, 400, 110), Err(MathError::ImbalancedDeposit));
+        assert_eq!(deposit_shares(0, 0, 0, 0, 1_000), Err(MathError::ZeroShares));
+    }
+
+    #[test]
+    fn withdrawals_round_down() {
+        assert_eq!(pro_rata(1_000, 1, 3), Ok(333));
+        assert_eq!(pro_rata(1_000, 3, 3), Ok(1_000));
+        assert_eq!(pro_rata(1_000, 0, 3), Err(MathError::ZeroShares));
+        assert_eq!(pro_rata(1_000, 4, 3), Err(MathError::InsufficientLiquidity));
+    }
+
+    #[test]
+    fn accumulators_never_pay_out_more_than_accrued() {
+        let growth = growth_per_share(1_000, 3).unwrap();
+        assert_eq!(accrued(1, growth, 0), Ok(333));
+        assert_eq!(accrued(3, growth, 0), Ok(999));
+        assert_eq!(accrued(3, growth, growth), Ok(0));
+        assert_eq!(growth_per_share(1_000, 0), Ok(0));
+        assert_eq!(growth_per_share(u128::MAX, 1), Err(MathError::Overflow));
+        assert_eq!(accrued(1, 0, growth), Err(MathError::Underflow));
+    }
+}
@@ -0,0 +1,18 @@
+use liquidity_pool_math::MathError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BacktestError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("csv: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("initial deposit rejected: {0}")]
+    InitialDeposit(MathError),
+    #[error("event at {0} has a side without an amount or an amount without a side")]
+    IncompleteTrade(i64),
+    #[error("event at {0} has a price that is not positive")]
+    InvalidPrice(i64),
+}
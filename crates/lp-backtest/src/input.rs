@@ -0,0 +1,66 @@
+use std::{fs::File, path::Path};
+
+use serde::Deserialize;
+
+use crate::error::BacktestError;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    UsdcIn,
+    SolIn,
+}
+
+/// one row of a price series or trade log. a price moves the market and lets an arbitrageur
+/// trade the pool back in line, a side and amount replays a user swap. a row may carry both,
+/// the trade runs first.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct Event {
+    pub timestamp: i64,
+    /// usdc per sol in whole tokens
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub side: Option<Side>,
+    /// swap amount in base units of the input mint, usdc swaps pay the fee on top
+    #[serde(default)]
+    pub amount: Option<u64>,
+}
+
+/// `.json` files hold an array of events, anything else is read as csv with a header row
+pub fn read_events(path: &Path) -> Result<Vec<Event>, BacktestError> {
+    let file = File::open(path)?;
+    let events = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_reader(file)?
+    } else {
+        csv::Reader::from_reader(file).deserialize().collect::<Result<_, _>>()?
+    };
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_price_series_and_trade_logs_in_both_formats() {
+        let dir = std::env::temp_dir().join(format!("lp-backtest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let csv = dir.join("events.csv");
+        std::fs::write(&csv, "timestamp,price,side,amount\n1,150.5,,\n2,,sol_in,1000\n").unwrap();
+        let json = dir.join("events.json");
+        std::fs::write(&json, r#"[{"timestamp":1,"price":150.5},{"timestamp":2,"side":"sol_in","amount":1000}]"#).unwrap();
+
+        let expected = vec![
+            Event { timestamp: 1, price: Some(150.5), side: None, amount: None },
+            Event { timestamp: 2, price: None, side: Some(Side::SolIn), amount: Some(1_000) },
+        ];
+        assert_eq!(read_events(&csv).unwrap(), expected);
+        assert_eq!(read_events(&json).unwrap(), expected);
+
+        std::fs::write(&csv, "timestamp,price\n1,not a price\n").unwrap();
+        assert!(matches!(read_events(&csv), Err(BacktestError::Csv(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+//! replays price series and trade logs against an in-memory pool that runs the same
+//! `liquidity-pool-math` curve and fee code as the program's swap, and reports how a
+//! single lp holding the whole pool would have done.
+
+pub mod error;
+pub mod input;
+
+use std::fmt;
+
+use liquidity_pool_math::{deposit_shares, swap_sol_for_usdc, swap_usdc_for_sol, SwapOutcome, BPS_SCALE};
+
+pub use error::BacktestError;
+pub use input::{read_events, Event, Side};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub fee_bps: u64,
+    /// initial deposit in base units
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    pub usdc_decimals: u8,
+    pub sol_decimals: u8,
+}
+
+/// the pool plus running totals. fees go to their own bucket like `fees_collected_usdc`.
+#[derive(Clone, Debug)]
+pub struct Simulator {
+    config: Config,
+    usdc_reserve: u64,
+    sol_reserve: u64,
+    fees_usdc: u64,
+    /// usdc per sol in whole tokens. the lp is measured from the first market price, or
+    /// from the pool's own price when the input has none
+    initial_price: f64,
+    market_price: Option<f64>,
+    trades: u64,
+    arbitrage_trades: u64,
+    rejected_trades: u64,
+    volume_usdc: u64,
+}
+
+/// values in whole usdc, marked at the last market price or the pool price without one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Report {
+    pub fee_bps: u64,
+    pub trades: u64,
+    pub arbitrage_trades: u64,
+    pub rejected_trades: u64,
+    pub volume_usdc: f64,
+    pub fees_usdc: f64,
+    pub initial_value: f64,
+    /// reserves plus fees
+    pub final_value: f64,
+    /// the initial deposit held outside the pool
+    pub hodl_value: f64,
+    pub pnl: f64,
+    /// reserves against holding, as a fraction, fees excluded
+    pub impermanent_loss: f64,
+    pub usdc_reserve: f64,
+    pub sol_reserve: f64,
+}
+
+impl Simulator {
+    pub fn new(config: Config) -> Result<Self, BacktestError> {
+        deposit_shares(0, 0, 0, config.usdc_amount, config.sol_amount).map_err(BacktestError::InitialDeposit)?;
+
+        let mut simulator = Simulator {
+            config,
+            usdc_reserve: config.usdc_amount,
+            sol_reserve: config.sol_amount,
+            fees_usdc: 0,
+            initial_price: 0.0,
+            market_price: None,
+            trades: 0,
+            arbitrage_trades: 0,
+            rejected_trades: 0,
+            volume_usdc: 0,
+        };
+        simulator.initial_price = simulator.pool_price();
+        Ok(simulator)
+    }
+
+    pub fn apply(&mut self, event: &Event) -> Result<(), BacktestError> {
+        match (event.side, event.amount) {
+            (Some(side), Some(amount)) => {
+                if self.swap(side, amount) {
+                    self.trades += 1;
+                } else {
+                    self.rejected_trades += 1;
+                }
+            }
+            (None, None) => {}
+            _ => return Err(BacktestError::IncompleteTrade(event.timestamp)),
+        }
+
+        if let Some(price) = event.price {
+            if !(price > 0.0 && price.is_finite()) {
+                return Err(BacktestError::InvalidPrice(event.timestamp));
+            }
+            if self.market_price.is_none() {
+                self.initial_price = price;
+            }
+            self.market_price = Some(price);
+            self.arbitrage(price);
+        }
+        Ok(())
+    }
+
+    /// runs `swap_usdc_for_sol` / `swap_sol_for_usdc` like process_swap does and books
+    /// the result. returns false when the program would have rejected the swap.
+    fn swap(&mut self, side: Side, amount: u64) -> bool {
+        let outcome = match side {
+            Side::UsdcIn => swap_usdc_for_sol(self.usdc_reserve, self.sol_reserve, amount, self.config.fee_bps),
+            Side::SolIn => swap_sol_for_usdc(self.usdc_reserve, self.sol_reserve, amount, self.config.fee_bps),
+        };
+        let Ok(SwapOutcome { amount_out, fee, usdc_reserve, sol_reserve }) = outcome else {
+            return false;
+        };
+        if amount_out == 0 {
+            return false;
+        }
+
+        let volume = match side {
+            Side::UsdcIn => amount,
+            Side::SolIn => amount_out + fee,
+        };
+        self.usdc_reserve = usdc_reserve;
+        self.sol_reserve = sol_reserve;
+        self.fees_usdc = self.fees_usdc.saturating_add(fee);
+        self.volume_usdc = self.volume_usdc.saturating_add(volume);
+        true
+    }
+
+    /// trades the pool to where the next unit would cost the arbitrageur more than the
+    /// market pays: pool price * (1 + fee) for buying sol, pool price * (1 - fee) for selling
+    fn arbitrage(&mut self, price: f64) {
+        let price = price * self.price_scale();
+        let fee = self.config.fee_bps as f64 / BPS_SCALE as f64;
+        let k = self.usdc_reserve as f64 * self.sol_reserve as f64;
+        let pool_price = self.usdc_reserve as f64 / self.sol_reserve as f64;
+
+        let trade = if price > pool_price * (1.0 + fee) {
+            let target_usdc = (k * price / (1.0 + fee)).sqrt();
+            (Side::UsdcIn, (target_usdc - self.usdc_reserve as f64) as u64)
+        } else if price < pool_price * (1.0 - fee) {
+            let target_sol = (k * (1.0 - fee) / price).sqrt();
+            (Side::SolIn, (target_sol - self.sol_reserve as f64) as u64)
+        } else {
+            return;
+        };
+
+        if trade.1 > 0 && self.swap(trade.0, trade.1) {
+            self.arbitrage_trades += 1;
+        }
+    }
+
+    pub fn report(&self) -> Report {
+        let usdc = |amount: u64| amount as f64 / 10f64.powi(self.config.usdc_decimals as i32);
+        let sol = |amount: u64| amount as f64 / 10f64.powi(self.config.sol_decimals as i32);
+
+        let mark = self.market_price.unwrap_or_else(|| self.pool_price());
+
+        let initial_value = usdc(self.config.usdc_amount) + sol(self.config.sol_amount) * self.initial_price;
+        let hodl_value = usdc(self.config.usdc_amount) + sol(self.config.sol_amount) * mark;
+        let reserves_value = usdc(self.usdc_reserve) + sol(self.sol_reserve) * mark;
+        let final_value = reserves_value + usdc(self.fees_usdc);
+
+        Report {
+            fee_bps: self.config.fee_bps,
+            trades: self.trades,
+            arbitrage_trades: self.arbitrage_trades,
+            rejected_trades: self.rejected_trades,
+            volume_usdc: usdc(self.volume_usdc),
+            fees_usdc: usdc(self.fees_usdc),
+            initial_value,
+            final_value,
+            hodl_value,
+            pnl: final_value - initial_value,
+            impermanent_loss: reserves_value / hodl_value - 1.0,
+            usdc_reserve: usdc(self.usdc_reserve),
+            sol_reserve: sol(self.sol_reserve),
+        }
+    }
+
+    /// whole-token price to base-unit price
+    fn price_scale(&self) -> f64 {
+        10f64.powi(self.config.usdc_decimals as i32 - self.config.sol_decimals as i32)
+    }
+
+    fn pool_price(&self) -> f64 {
+        self.usdc_reserve as f64 / self.sol_reserve as f64 / self.price_scale()
+    }
+}
+
+pub fn run(config: Config, events: &[Event]) -> Result<Report, BacktestError> {
+    let mut simulator = Simulator::new(config)?;
+    for event in events {
+        simulator.apply(event)?;
+    }
+    Ok(simulator.report())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "fee tier          {} bps", self.fee_bps)?;
+        writeln!(f, "trades            {} ({} arbitrage, {} rejected)", self.trades + self.arbitrage_trades, self.arbitrage_trades, self.rejected_trades)?;
+        writeln!(f, "volume            {:.2} usdc", self.volume_usdc)?;
+        writeln!(f, "fees earned       {:.2} usdc", self.fees_usdc)?;
+        writeln!(f, "final reserves    {:.2} usdc / {:.4} sol", self.usdc_reserve, self.sol_reserve)?;
+        writeln!(f, "initial value     {:.2} usdc", self.initial_value)?;
+        writeln!(f, "final value       {:.2} usdc", self.final_value)?;
+        writeln!(f, "hodl value        {:.2} usdc", self.hodl_value)?;
+        writeln!(f, "lp pnl            {:.2} usdc", self.pnl)?;
+        write!(f, "impermanent loss  {:.4}%", self.impermanent_loss * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: Config = Config {
+        fee_bps: 30,
+        usdc_amount: 1_500_000_000_000,
+        sol_amount: 10_000_000_000_000,
+        usdc_decimals: 6,
+        sol_decimals: 9,
+    };
+
+    fn price(timestamp: i64, price: f64) -> Event {
+        Event { timestamp, price: Some(price), side: None, amount: None }
+    }
+
+    #[test]
+    fn flat_market_has_no_loss() {
+        let report = run(CONFIG, &[price(0, 150.0), price(1, 150.1)]).unwrap();
+        assert_eq!((report.trades, report.arbitrage_trades), (0, 0));
+        assert_eq!(report.pnl, report.final_value - report.initial_value);
+        assert!(report.impermanent_loss.abs() < 1e-9);
+        assert!((report.initial_value - 3_000_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn arbitrage_tracks_the_market_and_earns_fees() {
+        let report = run(CONFIG, &[price(0, 150.0), price(1, 300.0), price(2, 150.0)]).unwrap();
+        assert_eq!(report.arbitrage_trades, 2);
+        assert!(report.fees_usdc > 0.0);
+        // back at the start price the reserves are whole again and fees are pure profit
+        assert!(report.impermanent_loss.abs() < 1e-3);
+        assert!(report.pnl > 0.0);
+
+        let report = run(CONFIG, &[price(0, 150.0), price(1, 600.0)]).unwrap();
+        // 4x move: 2 * sqrt(4) / (1 + 4) - 1 = -20%, less what the fee band keeps out
+        assert!((report.impermanent_loss + 0.2).abs() < 0.01);
+        let pool_price = report.usdc_reserve / report.sol_reserve;
+        assert!((pool_price - 600.0 / 1.003).abs() < 0.01);
+    }
+
+    #[test]
+    fn replays_trade_logs_with_the_program_math() {
+        let trade = |side, amount| Event { timestamp: 0, price: None, side: Some(side), amount: Some(amount) };
+        let report = run(CONFIG, &[trade(Side::UsdcIn, 1_000_000_000), trade(Side::SolIn, u64::MAX)]).unwrap();
+
+        let expected = swap_usdc_for_sol(CONFIG.usdc_amount, CONFIG.sol_amount, 1_000_000_000, 30).unwrap();
+        assert_eq!((report.trades, report.rejected_trades), (1, 1));
+        assert_eq!(report.fees_usdc, expected.fee as f64 / 1e6);
+        assert_eq!(report.volume_usdc, 1_000.0);
+
+        let incomplete = Event { timestamp: 7, price: None, side: Some(Side::SolIn), amount: None };
+        assert!(matches!(run(CONFIG, &[incomplete]), Err(BacktestError::IncompleteTrade(7))));
+    }
+}
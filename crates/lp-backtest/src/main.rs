@@ -0,0 +1,46 @@
+//! lp-backtest: replay a price series or trade log against the pool for one or more fee
+//! tiers and print how the lp would have done
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use lp_backtest::{read_events, run, Config};
+
+#[derive(Parser)]
+#[command(name = "lp-backtest", version, about = "Backtest liquidity pool fee tiers and initial ratios")]
+struct Cli {
+    /// csv with a header row or a json array, columns timestamp, price, side, amount
+    input: PathBuf,
+    /// fee tiers to compare, comma separated
+    #[arg(long, value_delimiter = ',', default_value = "30")]
+    fee_bps: Vec<u64>,
+    /// initial usdc deposit in base units
+    #[arg(long)]
+    usdc_amount: u64,
+    /// initial sol deposit in base units
+    #[arg(long)]
+    sol_amount: u64,
+    #[arg(long, default_value_t = 6)]
+    usdc_decimals: u8,
+    #[arg(long, default_value_t = 9)]
+    sol_decimals: u8,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let events = read_events(&cli.input)?;
+    println!("{} events from {}", events.len(), cli.input.display());
+
+    for fee_bps in cli.fee_bps {
+        let config = Config {
+            fee_bps,
+            usdc_amount: cli.usdc_amount,
+            sol_amount: cli.sol_amount,
+            usdc_decimals: cli.usdc_decimals,
+            sol_decimals: cli.sol_decimals,
+        };
+        println!("\n{}", run(config, &events)?);
+    }
+    Ok(())
+}
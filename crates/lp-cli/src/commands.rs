@@ -0,0 +1,456 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction, AccountDeserialize};
+use anchor_spl::token_interface::Mint;
+use anyhow::{anyhow, bail, Context as _, Result};
+use bytemuck::Zeroable;
+use liquidity_pool_client::{
+    decode_pool, decode_reward_vault, decode_user, instructions, quote_deposit, quote_fees, quote_rewards, quote_swap, quote_withdraw,
+    lock_boost_bps, Pool, PoolKeys, RewardVault, SwapQuote, User,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use crate::{PoolArgs, Side};
+
+pub struct Context {
+    rpc: RpcClient,
+    keypair: Option<PathBuf>,
+    dry_run: bool,
+}
+
+/// what the cli needs to know about a pool: its keys, the decoded account if it exists
+/// and the mint decimals for display
+struct PoolInfo {
+    keys: PoolKeys,
+    pool: Option<Pool>,
+    usdc_decimals: u8,
+    sol_decimals: u8,
+}
+
+impl PoolInfo {
+    fn pool(&self) -> Result<&Pool> {
+        self.pool.as_ref().ok_or_else(|| anyhow!("pool {} does not exist", self.keys.pool()))
+    }
+
+    fn usdc(&self, amount: u64) -> String {
+        ui_amount(amount, self.usdc_decimals)
+    }
+
+    fn sol(&self, amount: u64) -> String {
+        ui_amount(amount, self.sol_decimals)
+    }
+}
+
+impl Context {
+    pub fn new(url: &str, keypair: Option<PathBuf>, dry_run: bool) -> Self {
+        Context {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            keypair,
+            dry_run,
+        }
+    }
+
+    /// read lazily so the show commands work without a keypair on disk
+    fn signer(&self) -> Result<Keypair> {
+        let path = match &self.keypair {
+            Some(path) => path.clone(),
+            None => {
+                let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set, pass --keypair"))?;
+                PathBuf::from(home).join(".config/solana/id.json")
+            }
+        };
+        read_keypair_file(&path).map_err(|err| anyhow!("reading keypair {}: {err}", path.display()))
+    }
+
+    /// token programs come from the mint owners, so token-2022 mints need no extra flags
+    fn pool_info(&self, args: PoolArgs) -> Result<PoolInfo> {
+        let mut keys = PoolKeys::new(args.usdc_mint, args.sol_mint, args.fee_tier);
+        let accounts = self
+            .rpc
+            .get_multiple_accounts(&[keys.usdc_mint, keys.wrapped_sol_mint, keys.pool()])
+            .context("fetching pool accounts")?;
+
+        let usdc_mint = accounts[0].as_ref().ok_or_else(|| anyhow!("usdc mint {} not found", keys.usdc_mint))?;
+        let sol_mint = accounts[1].as_ref().ok_or_else(|| anyhow!("sol mint {} not found", keys.wrapped_sol_mint))?;
+        keys.usdc_token_program = usdc_mint.owner;
+        keys.sol_token_program = sol_mint.owner;
+
+        let pool = accounts[2].as_ref().map(|account| decode_pool(&account.data)).transpose()?;
+        keys.rewards_enabled = pool.is_some_and(|pool| pool.rewards_enabled != 0);
+
+        Ok(PoolInfo {
+            keys,
+            pool,
+            usdc_decimals: Mint::try_deserialize(&mut &usdc_mint.data[..])?.decimals,
+            sol_decimals: Mint::try_deserialize(&mut &sol_mint.data[..])?.decimals,
+        })
+    }
+
+    /// the pool's reward vault and the token program of its reward mint
+    fn reward_vault(&self, keys: &PoolKeys) -> Result<(RewardVault, Pubkey)> {
+        let address = keys.reward_vault();
+        let account = self.rpc.get_account(&address).with_context(|| format!("fetching reward vault {address}"))?;
+        let reward_vault = decode_reward_vault(&account.data)?;
+        let reward_token_program = self.rpc.get_account(&reward_vault.reward_mint).context("fetching reward mint")?.owner;
+        Ok((reward_vault, reward_token_program))
+    }
+
+    /// sends `ixs` in one transaction signed by the cli keypair, or only simulates it with --dry-run
+    fn send(&self, ixs: &[Instruction]) -> Result<()> {
+        self.send_signed(ixs, &[])
+    }
+
+    /// like `send`, with keypairs the instructions need on top of the cli one
+    fn send_signed(&self, ixs: &[Instruction], extra_signers: &[&Keypair]) -> Result<()> {
+        let signer = self.signer()?;
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let signers: Vec<&Keypair> = std::iter::once(&signer).chain(extra_signers.iter().copied()).collect();
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &signers, blockhash);
+
+        if self.dry_run {
+            let result = self.rpc.simulate_transaction(&tx)?.value;
+            for log in result.logs.unwrap_or_default() {
+                println!("  {log}");
+            }
+            if let Some(units) = result.units_consumed {
+                println!("compute units   {units}");
+            }
+            match result.err {
+                Some(err) => bail!("simulation failed: {err}"),
+                None => println!("simulation ok, nothing sent"),
+            }
+            return Ok(());
+        }
+
+        let signature = self.rpc.send_and_confirm_transaction(&tx)?;
+        println!("signature       {signature}");
+        Ok(())
+    }
+}
+
+pub fn create_pool(ctx: &Context, args: PoolArgs, usdc_amount: u64, sol_amount: u64, native_sol: bool) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    if info.pool.is_some() {
+        bail!("pool {} already exists, use deposit", info.keys.pool());
+    }
+
+    println!("pool            {}", info.keys.pool());
+    println!("initial shares  {}", quote_deposit(&Pool::zeroed(), usdc_amount, sol_amount)?);
+    ctx.send(&[instructions::deposit(&info.keys, &ctx.signer()?.pubkey(), usdc_amount, sol_amount, native_sol)])
+}
+
+pub fn deposit(ctx: &Context, args: PoolArgs, usdc_amount: u64, sol_amount: u64, native_sol: bool) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let shares = quote_deposit(info.pool()?, usdc_amount, sol_amount)?;
+
+    println!("shares          {shares}");
+    ctx.send(&[instructions::deposit(&info.keys, &ctx.signer()?.pubkey(), usdc_amount, sol_amount, native_sol)])
+}
+
+pub fn swap(ctx: &Context, args: PoolArgs, amount: u64, side: Side, slippage_bps: u64, native_sol: bool) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let min_amount_out = print_quote(&info, amount, side)?.min_amount_out(slippage_bps);
+
+    println!("min out         {min_amount_out}");
+    ctx.send(&[instructions::swap(&info.keys, &ctx.signer()?.pubkey(), amount, min_amount_out, side == Side::UsdcIn, native_sol)])
+}
+
+pub fn withdraw(ctx: &Context, args: PoolArgs, native_sol: bool) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let owner = ctx.signer()?.pubkey();
+
+    let user = fetch_user(ctx, &info.keys, &owner)?;
+    if user.locked_until > now() {
+        bail!("position is locked until {}", user.locked_until);
+    }
+    let quote = quote_withdraw(info.pool()?, &user)?;
+    println!("usdc out        {}", info.usdc(quote.usdc + quote.usdc_fees));
+    println!("sol out         {}", info.sol(quote.sol + quote.sol_fees));
+
+    // the program refuses to close a position that is still owed rewards
+    let mut ixs = Vec::new();
+    if info.keys.rewards_enabled {
+        let (reward_vault, reward_token_program) = ctx.reward_vault(&info.keys)?;
+        let rewards = quote_rewards(info.pool()?, &reward_vault, &user, now())?;
+        if rewards > 0 {
+            println!("rewards         {rewards}");
+            ixs.push(instructions::claim_rewards(&info.keys, &owner, &reward_vault.reward_mint, &reward_token_program));
+        }
+    }
+    ixs.push(instructions::withdraw(&info.keys, &owner, native_sol));
+    ctx.send(&ixs)
+}
+
+pub fn collect_fees(ctx: &Context, args: PoolArgs) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let owner = ctx.signer()?.pubkey();
+
+    let user = fetch_user(ctx, &info.keys, &owner)?;
+    let (usdc_fees, sol_fees) = quote_fees(info.pool()?, &user)?;
+    if usdc_fees == 0 && sol_fees == 0 {
+        bail!("no fees to collect");
+    }
+    println!("fees            {} usdc / {} sol", info.usdc(usdc_fees), info.sol(sol_fees));
+    ctx.send(&[instructions::collect_fees(&info.keys, &owner)])
+}
+
+pub fn open_position(ctx: &Context, args: PoolArgs, usdc_amount: u64, sol_amount: u64) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let shares = quote_deposit(info.pool()?, usdc_amount, sol_amount)?;
+    let position_mint = Keypair::new();
+
+    println!("shares          {shares}");
+    println!("position mint   {}", position_mint.pubkey());
+    println!("position        {}", info.keys.position(&position_mint.pubkey()));
+    let ix = instructions::open_position(&info.keys, &ctx.signer()?.pubkey(), &position_mint.pubkey(), usdc_amount, sol_amount);
+    ctx.send_signed(&[ix], &[&position_mint])
+}
+
+pub fn close_position(ctx: &Context, args: PoolArgs, mint: Pubkey) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let holder = ctx.signer()?.pubkey();
+
+    let position = fetch_position(ctx, &info.keys, &mint)?;
+    let quote = quote_withdraw(info.pool()?, &position)?;
+    println!("usdc out        {}", info.usdc(quote.usdc + quote.usdc_fees));
+    println!("sol out         {}", info.sol(quote.sol + quote.sol_fees));
+
+    // same as withdraw, pending rewards have to be claimed before the position closes
+    let mut ixs = Vec::new();
+    if info.keys.rewards_enabled {
+        let (reward_vault, reward_token_program) = ctx.reward_vault(&info.keys)?;
+        let rewards = quote_rewards(info.pool()?, &reward_vault, &position, now())?;
+        if rewards > 0 {
+            println!("rewards         {rewards}");
+            ixs.push(instructions::claim_position_rewards(&info.keys, &holder, &mint, &reward_vault.reward_mint, &reward_token_program));
+        }
+    }
+    ixs.push(instructions::close_position(&info.keys, &holder, &mint));
+    ctx.send(&ixs)
+}
+
+pub fn collect_position_fees(ctx: &Context, args: PoolArgs, mint: Pubkey) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+
+    let position = fetch_position(ctx, &info.keys, &mint)?;
+    let (usdc_fees, sol_fees) = quote_fees(info.pool()?, &position)?;
+    if usdc_fees == 0 && sol_fees == 0 {
+        bail!("no fees to collect");
+    }
+    println!("fees            {} usdc / {} sol", info.usdc(usdc_fees), info.sol(sol_fees));
+    ctx.send(&[instructions::collect_position_fees(&info.keys, &ctx.signer()?.pubkey(), &mint)])
+}
+
+/// the keeper reward goes to the signer's atas, a side without one is compounded in full
+pub fn compound(ctx: &Context, args: PoolArgs) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let pool = info.pool()?;
+    let keeper = ctx.signer()?.pubkey();
+
+    let atas = [info.keys.user_usdc_ata(&keeper), info.keys.user_sol_ata(&keeper)];
+    let existing = ctx.rpc.get_multiple_accounts(&atas)?;
+    let [usdc_ata, sol_ata] = [0, 1].map(|i| existing[i].is_some().then_some(atas[i]));
+
+    // fees some positions are still owed stay in the buckets, only the rest is swept
+    let bps = |ata: Option<Pubkey>| if ata.is_some() { pool.compound_reward_bps } else { 0 };
+    let mut copy = *pool;
+    let ((usdc, sol), (keeper_usdc, keeper_sol)) = copy.compound_fees(bps(usdc_ata), bps(sol_ata))?;
+    println!("compounded      {} usdc / {} sol", info.usdc(usdc), info.sol(sol));
+    println!("keeper reward   {} usdc / {} sol ({} bps)", info.usdc(keeper_usdc), info.sol(keeper_sol), pool.compound_reward_bps);
+    ctx.send(&[instructions::compound_fees(&info.keys, &keeper, usdc_ata, sol_ata)])
+}
+
+pub fn set_compound_reward(ctx: &Context, args: PoolArgs, reward_bps: u64) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    info.pool()?;
+    ctx.send(&[instructions::set_compound_reward(&info.keys, &ctx.signer()?.pubkey(), reward_bps)])
+}
+
+pub fn lock(ctx: &Context, args: PoolArgs, duration: i64) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let boost_bps = lock_boost_bps(duration)?;
+
+    println!("locked until    {}", now() + duration);
+    println!("reward boost    {boost_bps} bps");
+    ctx.send(&[instructions::lock_position(&info.keys, &ctx.signer()?.pubkey(), duration)])
+}
+
+pub fn expire_lock(ctx: &Context, args: PoolArgs, owner: Pubkey) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let user = fetch_user(ctx, &info.keys, &owner)?;
+    if user.boost_shares == 0 || user.locked_until > now() {
+        bail!("{owner} has no expired lock");
+    }
+
+    println!("boost dropped   {} shares", user.boost_shares);
+    ctx.send(&[instructions::expire_lock(&info.keys, &owner)])
+}
+
+pub fn init_rewards(ctx: &Context, args: PoolArgs, reward_mint: Pubkey, emission_rate: u64, start_time: i64, end_time: i64) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    info.pool()?;
+    let reward_token_program = ctx.rpc.get_account(&reward_mint).context("fetching reward mint")?.owner;
+    let total = emission_rate.saturating_mul(end_time.saturating_sub(start_time).max(0) as u64);
+
+    println!("reward vault    {}", info.keys.reward_vault());
+    println!("funding         {total} base units");
+    ctx.send(&[instructions::initialize_rewards(
+        &info.keys,
+        &ctx.signer()?.pubkey(),
+        &reward_mint,
+        &reward_token_program,
+        emission_rate,
+        start_time,
+        end_time,
+    )])
+}
+
+pub fn claim_rewards(ctx: &Context, args: PoolArgs) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    if !info.keys.rewards_enabled {
+        bail!("pool {} has no rewards", info.keys.pool());
+    }
+    let owner = ctx.signer()?.pubkey();
+
+    let user = fetch_user(ctx, &info.keys, &owner)?;
+    let (reward_vault, reward_token_program) = ctx.reward_vault(&info.keys)?;
+    println!("rewards         {}", quote_rewards(info.pool()?, &reward_vault, &user, now())?);
+    ctx.send(&[instructions::claim_rewards(&info.keys, &owner, &reward_vault.reward_mint, &reward_token_program)])
+}
+
+pub fn show_pool(ctx: &Context, args: PoolArgs) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let pool = info.pool()?;
+
+    println!("pool            {}", info.keys.pool());
+    println!("authority       {}", pool.authority);
+    println!("version         {}", pool.version);
+    println!("reserves        {} usdc / {} sol", info.usdc(pool.total_usdc_deposit), info.sol(pool.total_sol_deposit));
+    println!("fees owed       {} usdc / {} sol", info.usdc(pool.fees_collected_usdc), info.sol(pool.fees_collected_sol));
+    println!("price           {} usdc per sol", ui_price(&info, pool));
+
+    let mut copy = *pool;
+    let fee_bps = copy.swap_fee_bps(now())?;
+    if pool.dynamic_fee_enabled != 0 {
+        println!("swap fee        {fee_bps} bps (dynamic, base {} bps, {}-{} bps)", pool.liquidity_fees, pool.min_fee_bps, pool.max_fee_bps);
+    } else {
+        println!("swap fee        {fee_bps} bps");
+    }
+
+    if pool.compound_reward_bps > 0 {
+        println!("compound reward {} bps", pool.compound_reward_bps);
+    }
+    println!("total shares    {}", pool.total_shares);
+    if pool.total_shares > 0 {
+        // valued at a million shares, a single share usually rounds to nothing. fees are
+        // collected per position, so only the reserves count.
+        let shares = pool.total_shares.min(1_000_000);
+        let mut copy = *pool;
+        let (usdc, sol) = copy.remove_liquidity(shares)?;
+        println!("share value     {} usdc / {} sol per {shares} shares", info.usdc(usdc), info.sol(sol));
+    }
+    Ok(())
+}
+
+pub fn show_position(ctx: &Context, args: PoolArgs, owner: Option<Pubkey>, mint: Option<Pubkey>) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    let user = if let Some(mint) = mint {
+        println!("position        {}", info.keys.position(&mint));
+        println!("nft mint        {mint}");
+        fetch_position(ctx, &info.keys, &mint)?
+    } else {
+        let owner = match owner {
+            Some(owner) => owner,
+            None => ctx.signer()?.pubkey(),
+        };
+        println!("position        {}", info.keys.lp(&owner));
+        println!("owner           {owner}");
+        fetch_user(ctx, &info.keys, &owner)?
+    };
+
+    println!("deposited       {} usdc / {} sol", info.usdc(user.usdc_deposit), info.sol(user.sol_deposit));
+    println!("shares          {}", user.total_shares);
+    if user.locked_until > now() {
+        println!("locked until    {} ({} bps reward boost)", user.locked_until, user.lock_boost_bps);
+    }
+
+    let pool = info.pool()?;
+    if user.total_shares > 0 && user.total_shares <= pool.total_shares {
+        let quote = quote_withdraw(pool, &user)?;
+        println!("value           {} usdc / {} sol", info.usdc(quote.usdc), info.sol(quote.sol));
+        println!("fees earned     {} usdc / {} sol", info.usdc(quote.usdc_fees), info.sol(quote.sol_fees));
+        println!("pool share      {:.4}%", user.total_shares as f64 * 100.0 / pool.total_shares as f64);
+    }
+    if info.keys.rewards_enabled {
+        let (reward_vault, _) = ctx.reward_vault(&info.keys)?;
+        println!("rewards         {} of mint {}", quote_rewards(pool, &reward_vault, &user, now())?, reward_vault.reward_mint);
+    }
+    Ok(())
+}
+
+pub fn quote(ctx: &Context, args: PoolArgs, amount: u64, side: Side) -> Result<()> {
+    let info = ctx.pool_info(args)?;
+    print_quote(&info, amount, side).map(|_| ())
+}
+
+fn print_quote(info: &PoolInfo, amount: u64, side: Side) -> Result<SwapQuote> {
+    let pool = info.pool()?;
+    let quote = quote_swap(pool, amount, side == Side::UsdcIn, now())?;
+
+    let (amount_in, amount_out) = match side {
+        Side::UsdcIn => (format!("{} usdc", info.usdc(quote.amount_in)), format!("{} sol", info.sol(quote.amount_out))),
+        Side::SolIn => (format!("{} sol", info.sol(quote.amount_in)), format!("{} usdc", info.usdc(quote.amount_out))),
+    };
+    println!("in              {amount_in}");
+    println!("out             {amount_out}");
+    println!("fee             {} usdc ({} bps)", info.usdc(quote.fee), quote.fee_bps);
+    Ok(quote)
+}
+
+fn fetch_user(ctx: &Context, keys: &PoolKeys, owner: &Pubkey) -> Result<User> {
+    let address = keys.lp(owner);
+    let account = ctx
+        .rpc
+        .get_account_with_commitment(&address, ctx.rpc.commitment())?
+        .value
+        .ok_or_else(|| anyhow!("{owner} has no position"))?;
+    Ok(decode_user(&account.data)?)
+}
+
+fn fetch_position(ctx: &Context, keys: &PoolKeys, mint: &Pubkey) -> Result<User> {
+    let address = keys.position(mint);
+    let account = ctx
+        .rpc
+        .get_account_with_commitment(&address, ctx.rpc.commitment())?
+        .value
+        .ok_or_else(|| anyhow!("no open position for mint {mint}"))?;
+    Ok(decode_user(&account.data)?)
+}
+
+fn ui_price(info: &PoolInfo, pool: &Pool) -> String {
+    if pool.total_sol_deposit == 0 {
+        return "-".to_string();
+    }
+    let usdc = pool.total_usdc_deposit as f64 / 10f64.powi(info.usdc_decimals as i32);
+    let sol = pool.total_sol_deposit as f64 / 10f64.powi(info.sol_decimals as i32);
+    format!("{:.6}", usdc / sol)
+}
+
+fn ui_amount(amount: u64, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let scale = 10u64.pow(decimals as u32);
+    format!("{}.{:0width$}", amount / scale, amount % scale, width = decimals as usize)
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default()
+}
@@ -0,0 +1,220 @@
+//! lp-cli: create, fund, trade against and inspect liquidity pools over rpc.
+//! amounts are always base units of the mint.
+
+mod commands;
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+#[derive(Parser)]
+#[command(name = "lp-cli", version, about = "Manage liquidity pools over rpc")]
+struct Cli {
+    /// rpc endpoint, a local test validator by default
+    #[arg(long, short = 'u', env = "LP_RPC_URL", default_value = "http://127.0.0.1:8899", global = true)]
+    url: String,
+    /// signer keypair file, defaults to the solana cli keypair
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+    /// simulate the transaction and print its logs instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args, Clone, Copy)]
+pub struct PoolArgs {
+    #[arg(long)]
+    pub usdc_mint: Pubkey,
+    #[arg(long)]
+    pub sol_mint: Pubkey,
+    #[arg(long, default_value_t = 30)]
+    pub fee_tier: u16,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// pay usdc, receive sol
+    UsdcIn,
+    /// pay sol, receive usdc
+    SolIn,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// create a pool on a whitelisted fee tier with its first deposit
+    CreatePool {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        usdc_amount: u64,
+        #[arg(long)]
+        sol_amount: u64,
+        /// pay the sol side from lamports, the pool's sol mint has to be the native mint
+        #[arg(long)]
+        native_sol: bool,
+    },
+    /// add liquidity to an existing pool at its current ratio
+    Deposit {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        usdc_amount: u64,
+        #[arg(long)]
+        sol_amount: u64,
+        #[arg(long)]
+        native_sol: bool,
+    },
+    /// swap against the pool, prints the quote first
+    Swap {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, value_enum)]
+        side: Side,
+        /// how far below the quote the output may land before the swap fails
+        #[arg(long, default_value_t = 50)]
+        slippage_bps: u64,
+        #[arg(long)]
+        native_sol: bool,
+    },
+    /// burn the signer's whole position
+    Withdraw {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        native_sol: bool,
+    },
+    /// pay out the fees the signer's position earned, its shares stay in the pool
+    CollectFees {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+    /// deposit as a new transferable position, minted to the signer as an nft
+    OpenPosition {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        usdc_amount: u64,
+        #[arg(long)]
+        sol_amount: u64,
+    },
+    /// burn a held position nft and withdraw the whole position
+    ClosePosition {
+        #[command(flatten)]
+        pool: PoolArgs,
+        /// mint of the position nft
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// pay out the fees a held position nft earned
+    CollectPositionFees {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// fold the pool's fees into its reserves, anyone can call it
+    Compound {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+    /// set the part of the fees paid to whoever compounds them, the signer is the pool authority
+    SetCompoundReward {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        reward_bps: u64,
+    },
+    /// lock the signer's whole position for a reward boost, withdrawals fail until it expires
+    Lock {
+        #[command(flatten)]
+        pool: PoolArgs,
+        /// seconds, from a day up to a year
+        #[arg(long)]
+        duration: i64,
+    },
+    /// drop the reward boost of a lock that has ended, anyone can sign
+    ExpireLock {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        owner: Pubkey,
+    },
+    /// start liquidity mining on a pool, the signer is its authority and funds the whole schedule
+    InitRewards {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        reward_mint: Pubkey,
+        /// reward base units per second
+        #[arg(long)]
+        emission_rate: u64,
+        /// unix time, not in the past
+        #[arg(long)]
+        start_time: i64,
+        #[arg(long)]
+        end_time: i64,
+    },
+    /// pay out the signer's liquidity mining rewards
+    ClaimRewards {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+    /// reserves, price, fees and share value of a pool
+    ShowPool {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+    /// shares and current value of an lp position
+    ShowPosition {
+        #[command(flatten)]
+        pool: PoolArgs,
+        /// defaults to the signer
+        #[arg(long)]
+        owner: Option<Pubkey>,
+        /// show the nft position of this mint instead
+        #[arg(long, conflicts_with = "owner")]
+        mint: Option<Pubkey>,
+    },
+    /// offline quote against the pool's current state
+    Quote {
+        #[command(flatten)]
+        pool: PoolArgs,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, value_enum)]
+        side: Side,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let ctx = commands::Context::new(&cli.url, cli.keypair, cli.dry_run);
+
+    match cli.command {
+        Command::CreatePool { pool, usdc_amount, sol_amount, native_sol } => commands::create_pool(&ctx, pool, usdc_amount, sol_amount, native_sol),
+        Command::Deposit { pool, usdc_amount, sol_amount, native_sol } => commands::deposit(&ctx, pool, usdc_amount, sol_amount, native_sol),
+        Command::Swap { pool, amount, side, slippage_bps, native_sol } => commands::swap(&ctx, pool, amount, side, slippage_bps, native_sol),
+        Command::Withdraw { pool, native_sol } => commands::withdraw(&ctx, pool, native_sol),
+        Command::CollectFees { pool } => commands::collect_fees(&ctx, pool),
+        Command::OpenPosition { pool, usdc_amount, sol_amount } => commands::open_position(&ctx, pool, usdc_amount, sol_amount),
+        Command::ClosePosition { pool, mint } => commands::close_position(&ctx, pool, mint),
+        Command::CollectPositionFees { pool, mint } => commands::collect_position_fees(&ctx, pool, mint),
+        Command::Compound { pool } => commands::compound(&ctx, pool),
+        Command::SetCompoundReward { pool, reward_bps } => commands::set_compound_reward(&ctx, pool, reward_bps),
+        Command::Lock { pool, duration } => commands::lock(&ctx, pool, duration),
+        Command::ExpireLock { pool, owner } => commands::expire_lock(&ctx, pool, owner),
+        Command::InitRewards { pool, reward_mint, emission_rate, start_time, end_time } => {
+            commands::init_rewards(&ctx, pool, reward_mint, emission_rate, start_time, end_time)
+        }
+        Command::ClaimRewards { pool } => commands::claim_rewards(&ctx, pool),
+        Command::ShowPool { pool } => commands::show_pool(&ctx, pool),
+        Command::ShowPosition { pool, owner, mint } => commands::show_position(&ctx, pool, owner, mint),
+        Command::Quote { pool, amount, side } => commands::quote(&ctx, pool, amount, side),
+    }
+}
@@ -0,0 +1,231 @@
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use liquidity_pool::{
+    events::{CompoundEvent, DepositEvent, FeeCollectEvent, FlashLoanEvent, ReconcileEvent, SwapEvent, WithdrawEvent},
+    instruction,
+};
+use solana_transaction_status_client_types::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiMessage};
+
+use crate::error::IndexerError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolEvent {
+    Deposit(DepositEvent),
+    Swap(SwapEvent),
+    Withdraw(WithdrawEvent),
+    Compound(CompoundEvent),
+    Collect(FeeCollectEvent),
+    Reconcile(ReconcileEvent),
+    FlashLoan(FlashLoanEvent),
+}
+
+impl PoolEvent {
+    pub fn pool(&self) -> Pubkey {
+        match self {
+            PoolEvent::Deposit(event) => event.pool,
+            PoolEvent::Swap(event) => event.pool,
+            PoolEvent::Withdraw(event) => event.pool,
+            PoolEvent::Compound(event) => event.pool,
+            PoolEvent::Collect(event) => event.pool,
+            PoolEvent::Reconcile(event) => event.pool,
+            PoolEvent::FlashLoan(event) => event.pool,
+        }
+    }
+
+    /// (usdc, sol) reserves right after the event, none when it leaves them alone
+    pub fn reserves(&self) -> Option<(u64, u64)> {
+        match self {
+            PoolEvent::Deposit(event) => Some((event.usdc_reserve, event.sol_reserve)),
+            PoolEvent::Swap(event) => Some((event.usdc_reserve, event.sol_reserve)),
+            PoolEvent::Withdraw(event) => Some((event.usdc_reserve, event.sol_reserve)),
+            PoolEvent::Compound(event) => Some((event.usdc_reserve, event.sol_reserve)),
+            PoolEvent::Reconcile(event) => Some((event.usdc_reserve, event.sol_reserve)),
+            PoolEvent::FlashLoan(event) => Some((event.usdc_reserve, event.sol_reserve)),
+            PoolEvent::Collect(_) => None,
+        }
+    }
+}
+
+/// a successful transaction that touched the program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// names of the top level instructions sent to the program
+    pub instructions: Vec<&'static str>,
+    pub events: Vec<PoolEvent>,
+}
+
+const INSTRUCTIONS: [(&[u8], &str); 26] = [
+    (instruction::InitializeConfig::DISCRIMINATOR, "initialize_config"),
+    (instruction::AddFeeTier::DISCRIMINATOR, "add_fee_tier"),
+    (instruction::RemoveFeeTier::DISCRIMINATOR, "remove_fee_tier"),
+    (instruction::Deposit::DISCRIMINATOR, "deposit"),
+    (instruction::Swap::DISCRIMINATOR, "swap"),
+    (instruction::SwapRoute::DISCRIMINATOR, "swap_route"),
+    (instruction::Withdraw::DISCRIMINATOR, "withdraw"),
+    (instruction::FlashBorrow::DISCRIMINATOR, "flash_borrow"),
+    (instruction::FlashRepay::DISCRIMINATOR, "flash_repay"),
+    (instruction::FlashSwap::DISCRIMINATOR, "flash_swap"),
+    (instruction::Sync::DISCRIMINATOR, "sync"),
+    (instruction::Skim::DISCRIMINATOR, "skim"),
+    (instruction::MigrateLegacyPool::DISCRIMINATOR, "migrate_legacy_pool"),
+    (instruction::MigratePosition::DISCRIMINATOR, "migrate_position"),
+    (instruction::SetDynamicFee::DISCRIMINATOR, "set_dynamic_fee"),
+    (instruction::InitializeRewards::DISCRIMINATOR, "initialize_rewards"),
+    (instruction::ClaimRewards::DISCRIMINATOR, "claim_rewards"),
+    (instruction::LockPosition::DISCRIMINATOR, "lock_position"),
+    (instruction::ExpireLock::DISCRIMINATOR, "expire_lock"),
+    (instruction::SetCompoundReward::DISCRIMINATOR, "set_compound_reward"),
+    (instruction::CompoundFees::DISCRIMINATOR, "compound_fees"),
+    (instruction::CollectFees::DISCRIMINATOR, "collect_fees"),
+    (instruction::OpenPosition::DISCRIMINATOR, "open_position"),
+    (instruction::ClosePosition::DISCRIMINATOR, "close_position"),
+    (instruction::CollectPositionFees::DISCRIMINATOR, "collect_position_fees"),
+    (instruction::ClaimPositionRewards::DISCRIMINATOR, "claim_position_rewards"),
+];
+
+pub fn instruction_name(data: &[u8]) -> Option<&'static str> {
+    INSTRUCTIONS.iter().find(|(discriminator, _)| data.starts_with(discriminator)).map(|(_, name)| *name)
+}
+
+/// decodes the payload of a `Program data:` log line
+pub fn decode_event(data: &[u8]) -> Option<PoolEvent> {
+    let (discriminator, mut body) = data.split_at_checked(8)?;
+    let event = if discriminator == DepositEvent::DISCRIMINATOR {
+        PoolEvent::Deposit(DepositEvent::deserialize(&mut body).ok()?)
+    } else if discriminator == SwapEvent::DISCRIMINATOR {
+        PoolEvent::Swap(SwapEvent::deserialize(&mut body).ok()?)
+    } else if discriminator == WithdrawEvent::DISCRIMINATOR {
+        PoolEvent::Withdraw(WithdrawEvent::deserialize(&mut body).ok()?)
+    } else if discriminator == CompoundEvent::DISCRIMINATOR {
+        PoolEvent::Compound(CompoundEvent::deserialize(&mut body).ok()?)
+    } else if discriminator == FeeCollectEvent::DISCRIMINATOR {
+        PoolEvent::Collect(FeeCollectEvent::deserialize(&mut body).ok()?)
+    } else if discriminator == ReconcileEvent::DISCRIMINATOR {
+        PoolEvent::Reconcile(ReconcileEvent::deserialize(&mut body).ok()?)
+    } else if discriminator == FlashLoanEvent::DISCRIMINATOR {
+        PoolEvent::FlashLoan(FlashLoanEvent::deserialize(&mut body).ok()?)
+    } else {
+        return None;
+    };
+    Some(event)
+}
+
+/// events logged by the program itself. the invoke stack is followed so data logged by
+/// other programs, including ones the pool calls into, is never read as ours.
+pub fn events_from_logs(logs: &[String]) -> Vec<PoolEvent> {
+    let program_id = liquidity_pool::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let Some(rest) = log.strip_prefix("Program ") else { continue };
+
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                if let Some(event) = STANDARD.decode(data).ok().as_deref().and_then(decode_event) {
+                    events.push(event);
+                }
+            }
+        } else if let Some((program, _)) = rest.split_once(" invoke [") {
+            stack.push(program);
+        } else if rest.ends_with(" success") || rest.contains(" failed: ") {
+            stack.pop();
+        }
+    }
+    events
+}
+
+/// `None` for failed transactions and ones that never called the program. transactions
+/// have to be fetched with json encoding.
+pub fn decode_transaction(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Option<IndexedTransaction>, IndexerError> {
+    let EncodedTransaction::Json(ui_tx) = &tx.transaction.transaction else {
+        return Err(IndexerError::UnsupportedEncoding);
+    };
+    let UiMessage::Raw(message) = &ui_tx.message else {
+        return Err(IndexerError::UnsupportedEncoding);
+    };
+    let meta = tx.transaction.meta.as_ref().ok_or(IndexerError::MissingMeta)?;
+    if meta.err.is_some() {
+        return Ok(None);
+    }
+
+    let program_id = liquidity_pool::ID.to_string();
+    let mut instructions = Vec::new();
+    for ix in &message.instructions {
+        if message.account_keys.get(ix.program_id_index as usize) != Some(&program_id) {
+            continue;
+        }
+        let data = bs58::decode(&ix.data).into_vec().map_err(|_| IndexerError::InvalidInstructionData)?;
+        instructions.push(instruction_name(&data).unwrap_or("unknown"));
+    }
+
+    let logs: Option<Vec<String>> = meta.log_messages.clone().into();
+    let events = events_from_logs(&logs.unwrap_or_default());
+    if instructions.is_empty() && events.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(IndexedTransaction {
+        signature: ui_tx.signatures.first().cloned().ok_or(IndexerError::MissingSignature)?,
+        slot: tx.slot,
+        block_time: tx.block_time,
+        instructions,
+        events,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{Event, InstructionData};
+
+    fn swap_event() -> SwapEvent {
+        SwapEvent {
+            pool: Pubkey::new_unique(),
+            trader: Pubkey::new_unique(),
+            usdc_in: true,
+            amount_in: 1_003,
+            amount_out: 10,
+            usdc_fee: 3,
+            sol_fee: 0,
+            usdc_reserve: 101_000,
+            sol_reserve: 990,
+        }
+    }
+
+    fn data_log(data: Vec<u8>) -> String {
+        format!("Program data: {}", STANDARD.encode(data))
+    }
+
+    #[test]
+    fn only_reads_events_logged_by_the_program() {
+        let program = liquidity_pool::ID;
+        let event = swap_event();
+        let logs = vec![
+            format!("Program {program} invoke [1]"),
+            "Program log: Instruction: Swap".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+            data_log(event.data()),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+            data_log(event.data()),
+            "Program data: not base64".to_string(),
+            data_log(vec![1, 2, 3]),
+            format!("Program {program} consumed 20000 of 200000 compute units"),
+            format!("Program {program} success"),
+            data_log(event.data()),
+        ];
+
+        assert_eq!(events_from_logs(&logs), vec![PoolEvent::Swap(event)]);
+    }
+
+    #[test]
+    fn names_instructions_by_discriminator() {
+        let data = instruction::Swap { swap_amount: 5, min_amount_out: 0, native_sol: false }.data();
+        assert_eq!(instruction_name(&data), Some("swap"));
+        assert_eq!(instruction_name(&instruction::SwapRoute { amount_in: 1, min_amount_out: 1, hops: 1 }.data()), Some("swap_route"));
+        assert_eq!(instruction_name(&[0; 8]), None);
+    }
+}
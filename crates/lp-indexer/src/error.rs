@@ -0,0 +1,29 @@
+use solana_rpc_client_api::client_error::Error as ClientError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("sqlite: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    // boxed, the rpc error is several hundred bytes
+    #[error("rpc: {0}")]
+    Rpc(Box<ClientError>),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("transactions have to be fetched with json encoding")]
+    UnsupportedEncoding,
+    #[error("transaction has no status meta")]
+    MissingMeta,
+    #[error("transaction has no signature")]
+    MissingSignature,
+    #[error("instruction data is not base58")]
+    InvalidInstructionData,
+}
+
+impl From<ClientError> for IndexerError {
+    fn from(err: ClientError) -> Self {
+        IndexerError::Rpc(Box::new(err))
+    }
+}
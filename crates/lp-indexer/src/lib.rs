@@ -0,0 +1,37 @@
+//! indexes the liquidity pool program into sqlite. transactions come from an rpc node or a
+//! json dump, their `Program data:` logs are decoded into the program's events and written
+//! as deposits, swaps, withdrawals and per-slot reserve snapshots.
+
+pub mod decode;
+pub mod error;
+pub mod source;
+pub mod store;
+
+use solana_transaction_status_client_types::EncodedConfirmedTransactionWithStatusMeta;
+
+pub use decode::{decode_transaction, IndexedTransaction, PoolEvent};
+pub use error::IndexerError;
+pub use store::{LpAction, Store, Tvl, Volume};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub transactions: usize,
+    pub events: usize,
+    /// failed, unrelated or already indexed
+    pub skipped: usize,
+}
+
+/// decodes and stores `txs`, which have to be ordered oldest first
+pub fn index(store: &mut Store, txs: &[EncodedConfirmedTransactionWithStatusMeta]) -> Result<Summary, IndexerError> {
+    let mut summary = Summary::default();
+    for tx in txs {
+        match decode_transaction(tx)? {
+            Some(decoded) if store.insert(&decoded)? => {
+                summary.transactions += 1;
+                summary.events += decoded.events.len();
+            }
+            _ => summary.skipped += 1,
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,100 @@
+//! lp-indexer: fill a sqlite database with pool history and query it
+
+use std::{path::PathBuf, thread, time::Duration};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use lp_indexer::{index, source, Store};
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_client::RpcClient;
+
+#[derive(Parser)]
+#[command(name = "lp-indexer", version, about = "Index liquidity pool history into SQLite")]
+struct Cli {
+    #[arg(long, default_value = "lp-index.sqlite", global = true)]
+    db: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// index a json array of getTransaction results
+    Import { file: PathBuf },
+    /// index every program transaction since the last indexed one
+    Sync {
+        #[arg(long, short = 'u', default_value = "http://127.0.0.1:8899")]
+        url: String,
+        /// keep polling every this many seconds
+        #[arg(long)]
+        poll_secs: Option<u64>,
+    },
+    /// swap volume and fees, amounts in base units
+    Volume {
+        #[arg(long)]
+        pool: Option<Pubkey>,
+        /// unix time
+        #[arg(long)]
+        since: Option<i64>,
+    },
+    /// latest reserves of every pool
+    Tvl,
+    /// deposits and withdrawals of one lp
+    History {
+        #[arg(long)]
+        owner: Pubkey,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut store = Store::open(&cli.db)?;
+
+    match cli.command {
+        Command::Import { file } => {
+            let summary = index(&mut store, &source::read_dump(&file)?)?;
+            println!("indexed {} transactions with {} events, skipped {}", summary.transactions, summary.events, summary.skipped);
+        }
+        Command::Sync { url, poll_secs } => {
+            let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            loop {
+                let txs = source::fetch_since(&rpc, store.last_signature()?.as_deref())?;
+                let summary = index(&mut store, &txs)?;
+                println!("indexed {} transactions with {} events, skipped {}", summary.transactions, summary.events, summary.skipped);
+
+                let Some(secs) = poll_secs else { break };
+                thread::sleep(Duration::from_secs(secs));
+            }
+        }
+        Command::Volume { pool, since } => {
+            let volume = store.volume(pool.as_ref(), since)?;
+            println!("swaps     {}", volume.swaps);
+            println!("usdc      {}", volume.usdc);
+            println!("sol       {}", volume.sol);
+            println!("fees      {} usdc / {} sol", volume.fees_usdc, volume.fees_sol);
+        }
+        Command::Tvl => {
+            for tvl in store.tvl()? {
+                println!("{}  slot {}  {} usdc  {} sol", tvl.pool, tvl.slot, tvl.usdc_reserve, tvl.sol_reserve);
+            }
+        }
+        Command::History { owner } => {
+            for action in store.lp_history(&owner)? {
+                println!(
+                    "slot {}  {:<8}  pool {}  shares {}  {} usdc  {} sol  fees {} usdc {} sol  {}",
+                    action.slot,
+                    action.kind,
+                    action.pool,
+                    action.shares,
+                    action.usdc_amount,
+                    action.sol_amount,
+                    action.usdc_fees,
+                    action.sol_fees,
+                    action.signature
+                );
+            }
+        }
+    }
+    Ok(())
+}
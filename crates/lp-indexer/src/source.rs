@@ -0,0 +1,48 @@
+use std::{fs::File, path::Path, str::FromStr};
+
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_rpc_client_api::config::RpcTransactionConfig;
+use solana_signature::Signature;
+use solana_transaction_status_client_types::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+
+use crate::error::IndexerError;
+
+/// a json array of `getTransaction` results fetched with json encoding
+pub fn read_dump(path: &Path) -> Result<Vec<EncodedConfirmedTransactionWithStatusMeta>, IndexerError> {
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+/// every successful program transaction newer than `until`, oldest first
+pub fn fetch_since(rpc: &RpcClient, until: Option<&str>) -> Result<Vec<EncodedConfirmedTransactionWithStatusMeta>, IndexerError> {
+    let commitment = CommitmentConfig::confirmed();
+    let until = until.map(Signature::from_str).transpose().ok().flatten();
+
+    // signatures come newest first in pages of up to 1000
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = rpc.get_signatures_for_address_with_config(
+            &liquidity_pool::ID,
+            GetConfirmedSignaturesForAddress2Config { before, until, limit: None, commitment: Some(commitment) },
+        )?;
+        let Some(last) = page.last() else { break };
+        before = Signature::from_str(&last.signature).ok();
+        signatures.extend(page.into_iter().filter(|status| status.err.is_none()).map(|status| status.signature));
+        if before.is_none() {
+            break;
+        }
+    }
+
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+    };
+    signatures
+        .iter()
+        .rev()
+        .filter_map(|signature| Signature::from_str(signature).ok())
+        .map(|signature| Ok(rpc.get_transaction_with_config(&signature, config)?))
+        .collect()
+}
@@ -0,0 +1,454 @@
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    decode::{IndexedTransaction, PoolEvent},
+    error::IndexerError,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    instructions TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deposits (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    usdc_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL,
+    shares INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS swaps (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    trader TEXT NOT NULL,
+    usdc_in INTEGER NOT NULL,
+    amount_in INTEGER NOT NULL,
+    amount_out INTEGER NOT NULL,
+    usdc_fee INTEGER NOT NULL,
+    sol_fee INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS withdrawals (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    shares INTEGER NOT NULL,
+    usdc_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL,
+    usdc_fees INTEGER NOT NULL,
+    sol_fees INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS compounds (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    keeper TEXT NOT NULL,
+    usdc_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL,
+    keeper_usdc INTEGER NOT NULL,
+    keeper_sol INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fee_collections (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    usdc_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS reconciles (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    skim INTEGER NOT NULL,
+    usdc_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS flash_loans (
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    pool TEXT NOT NULL,
+    borrower TEXT NOT NULL,
+    usdc_amount INTEGER NOT NULL,
+    sol_amount INTEGER NOT NULL,
+    usdc_fee INTEGER NOT NULL,
+    sol_fee INTEGER NOT NULL
+);
+-- reserves at the end of each slot the pool was touched in
+CREATE TABLE IF NOT EXISTS reserve_snapshots (
+    pool TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    usdc_reserve INTEGER NOT NULL,
+    sol_reserve INTEGER NOT NULL,
+    PRIMARY KEY (pool, slot)
+);
+CREATE INDEX IF NOT EXISTS swaps_pool ON swaps (pool, block_time);
+CREATE INDEX IF NOT EXISTS deposits_owner ON deposits (owner);
+CREATE INDEX IF NOT EXISTS withdrawals_owner ON withdrawals (owner);
+CREATE INDEX IF NOT EXISTS fee_collections_owner ON fee_collections (owner);
+";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Volume {
+    pub swaps: u64,
+    /// usdc entering or leaving the curve, fees included
+    pub usdc: u64,
+    pub sol: u64,
+    pub fees_usdc: u64,
+    pub fees_sol: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tvl {
+    pub pool: String,
+    pub slot: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LpAction {
+    /// "deposit", "withdraw" or "collect"
+    pub kind: String,
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub pool: String,
+    pub shares: u64,
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    /// fees paid out on withdraw or collect, zero for deposits
+    pub usdc_fees: u64,
+    pub sol_fees: u64,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, IndexerError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// writes a transaction and its events in one sqlite transaction. returns false when
+    /// the signature was already indexed, so re-running over the same range is harmless.
+    /// transactions have to be inserted oldest first for the snapshots to end up right.
+    pub fn insert(&mut self, tx: &IndexedTransaction) -> Result<bool, IndexerError> {
+        let db = self.conn.transaction()?;
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time, instructions) VALUES (?1, ?2, ?3, ?4)",
+            params![tx.signature, tx.slot, tx.block_time, tx.instructions.join(",")],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        for event in &tx.events {
+            match event {
+                PoolEvent::Deposit(e) => db.execute(
+                    "INSERT INTO deposits VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![tx.signature, tx.slot, tx.block_time, e.pool.to_string(), e.owner.to_string(), e.usdc_amount, e.sol_amount, e.shares],
+                )?,
+                PoolEvent::Swap(e) => db.execute(
+                    "INSERT INTO swaps VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        tx.signature,
+                        tx.slot,
+                        tx.block_time,
+                        e.pool.to_string(),
+                        e.trader.to_string(),
+                        e.usdc_in,
+                        e.amount_in,
+                        e.amount_out,
+                        e.usdc_fee,
+                        e.sol_fee
+                    ],
+                )?,
+                PoolEvent::Withdraw(e) => db.execute(
+                    "INSERT INTO withdrawals VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        tx.signature,
+                        tx.slot,
+                        tx.block_time,
+                        e.pool.to_string(),
+                        e.owner.to_string(),
+                        e.shares,
+                        e.usdc_amount,
+                        e.sol_amount,
+                        e.usdc_fees,
+                        e.sol_fees
+                    ],
+                )?,
+                PoolEvent::Compound(e) => db.execute(
+                    "INSERT INTO compounds VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        tx.signature,
+                        tx.slot,
+                        tx.block_time,
+                        e.pool.to_string(),
+                        e.keeper.to_string(),
+                        e.usdc_amount,
+                        e.sol_amount,
+                        e.keeper_usdc,
+                        e.keeper_sol
+                    ],
+                )?,
+                PoolEvent::Collect(e) => db.execute(
+                    "INSERT INTO fee_collections VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![tx.signature, tx.slot, tx.block_time, e.pool.to_string(), e.owner.to_string(), e.usdc_amount, e.sol_amount],
+                )?,
+                PoolEvent::Reconcile(e) => db.execute(
+                    "INSERT INTO reconciles VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![tx.signature, tx.slot, tx.block_time, e.pool.to_string(), e.authority.to_string(), e.skim, e.usdc_amount, e.sol_amount],
+                )?,
+                PoolEvent::FlashLoan(e) => db.execute(
+                    "INSERT INTO flash_loans VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        tx.signature,
+                        tx.slot,
+                        tx.block_time,
+                        e.pool.to_string(),
+                        e.borrower.to_string(),
+                        e.usdc_amount,
+                        e.sol_amount,
+                        e.usdc_fee,
+                        e.sol_fee
+                    ],
+                )?,
+            };
+
+            if let Some((usdc_reserve, sol_reserve)) = event.reserves() {
+                db.execute(
+                    "INSERT OR REPLACE INTO reserve_snapshots VALUES (?1, ?2, ?3, ?4)",
+                    params![event.pool().to_string(), tx.slot, usdc_reserve, sol_reserve],
+                )?;
+            }
+        }
+
+        db.commit()?;
+        Ok(true)
+    }
+
+    /// newest indexed signature, where the next rpc backfill stops
+    pub fn last_signature(&self) -> Result<Option<String>, IndexerError> {
+        let signature = self
+            .conn
+            .query_row("SELECT signature FROM transactions ORDER BY slot DESC, rowid DESC LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        Ok(signature)
+    }
+
+    /// swap volume, for one pool or all of them, since a unix time
+    pub fn volume(&self, pool: Option<&Pubkey>, since: Option<i64>) -> Result<Volume, IndexerError> {
+        let volume = self.conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(CASE WHEN usdc_in THEN amount_in ELSE amount_out + usdc_fee END), 0),
+                    COALESCE(SUM(CASE WHEN usdc_in THEN amount_out ELSE amount_in END), 0),
+                    COALESCE(SUM(usdc_fee), 0),
+                    COALESCE(SUM(sol_fee), 0)
+             FROM swaps
+             WHERE (?1 IS NULL OR pool = ?1) AND (?2 IS NULL OR block_time >= ?2)",
+            params![pool.map(Pubkey::to_string), since],
+            |row| Ok(Volume { swaps: row.get(0)?, usdc: row.get(1)?, sol: row.get(2)?, fees_usdc: row.get(3)?, fees_sol: row.get(4)? }),
+        )?;
+        Ok(volume)
+    }
+
+    /// latest reserves of every indexed pool
+    pub fn tvl(&self) -> Result<Vec<Tvl>, IndexerError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pool, slot, usdc_reserve, sol_reserve FROM reserve_snapshots s
+             WHERE slot = (SELECT MAX(slot) FROM reserve_snapshots WHERE pool = s.pool)
+             ORDER BY pool",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Tvl { pool: row.get(0)?, slot: row.get(1)?, usdc_reserve: row.get(2)?, sol_reserve: row.get(3)? })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// deposits, withdrawals and fee collections of one lp, oldest first
+    pub fn lp_history(&self, owner: &Pubkey) -> Result<Vec<LpAction>, IndexerError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT 'deposit', signature, slot, block_time, pool, shares, usdc_amount, sol_amount, 0, 0, rowid FROM deposits WHERE owner = ?1
+             UNION ALL
+             SELECT 'withdraw', signature, slot, block_time, pool, shares, usdc_amount, sol_amount, usdc_fees, sol_fees, rowid FROM withdrawals WHERE owner = ?1
+             UNION ALL
+             SELECT 'collect', signature, slot, block_time, pool, 0, 0, 0, usdc_amount, sol_amount, rowid FROM fee_collections WHERE owner = ?1
+             ORDER BY slot, rowid",
+        )?;
+        let rows = stmt.query_map([owner.to_string()], |row| {
+            Ok(LpAction {
+                kind: row.get(0)?,
+                signature: row.get(1)?,
+                slot: row.get(2)?,
+                block_time: row.get(3)?,
+                pool: row.get(4)?,
+                shares: row.get(5)?,
+                usdc_amount: row.get(6)?,
+                sol_amount: row.get(7)?,
+                usdc_fees: row.get(8)?,
+                sol_fees: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use liquidity_pool::events::{CompoundEvent, DepositEvent, FeeCollectEvent, FlashLoanEvent, ReconcileEvent, SwapEvent, WithdrawEvent};
+
+    fn tx(signature: &str, slot: u64, block_time: i64, events: Vec<PoolEvent>) -> IndexedTransaction {
+        IndexedTransaction { signature: signature.to_string(), slot, block_time: Some(block_time), instructions: vec!["swap"], events }
+    }
+
+    fn swap(pool: Pubkey, usdc_in: bool, amount_in: u64, amount_out: u64, fees: (u64, u64), reserves: (u64, u64)) -> PoolEvent {
+        PoolEvent::Swap(SwapEvent {
+            pool,
+            trader: Pubkey::new_unique(),
+            usdc_in,
+            amount_in,
+            amount_out,
+            usdc_fee: fees.0,
+            sol_fee: fees.1,
+            usdc_reserve: reserves.0,
+            sol_reserve: reserves.1,
+        })
+    }
+
+    #[test]
+    fn indexes_events_and_answers_queries() {
+        let mut store = Store::open_in_memory().unwrap();
+        let (pool, other_pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let deposit = PoolEvent::Deposit(DepositEvent {
+            pool,
+            owner,
+            usdc_amount: 100_000,
+            sol_amount: 1_000,
+            shares: 10_000,
+            usdc_reserve: 100_000,
+            sol_reserve: 1_000,
+        });
+        assert!(store.insert(&tx("a", 1, 100, vec![deposit])).unwrap());
+        assert!(!store.insert(&tx("a", 1, 100, vec![])).unwrap());
+
+        // the last one is a flash swap repaid in sol, its fee stays on the sol side
+        let swaps = vec![
+            swap(pool, true, 1_003, 10, (3, 0), (101_000, 990)),
+            swap(pool, false, 10, 995, (2, 0), (100_005, 1_000)),
+            swap(pool, false, 11, 5, (0, 1), (100_000, 1_011)),
+        ];
+        store.insert(&tx("b", 2, 200, swaps)).unwrap();
+        store.insert(&tx("c", 3, 300, vec![swap(other_pool, true, 50, 1, (1, 0), (5_000, 99))])).unwrap();
+
+        // collecting fees leaves the reserves, and so the snapshots, alone
+        let collect = PoolEvent::Collect(FeeCollectEvent { pool, owner, usdc_amount: 3, sol_amount: 0 });
+        store.insert(&tx("c2", 3, 300, vec![collect])).unwrap();
+
+        let withdraw = PoolEvent::Withdraw(WithdrawEvent {
+            pool,
+            owner,
+            shares: 10_000,
+            usdc_amount: 100_005,
+            sol_amount: 1_000,
+            usdc_fees: 5,
+            sol_fees: 0,
+            usdc_reserve: 0,
+            sol_reserve: 0,
+        });
+        store.insert(&tx("d", 4, 400, vec![withdraw])).unwrap();
+
+        let compound = PoolEvent::Compound(CompoundEvent {
+            pool: other_pool,
+            keeper: owner,
+            usdc_amount: 1,
+            sol_amount: 0,
+            keeper_usdc: 0,
+            keeper_sol: 0,
+            usdc_reserve: 5_001,
+            sol_reserve: 99,
+        });
+        store.insert(&tx("e", 5, 500, vec![compound])).unwrap();
+
+        assert_eq!(store.volume(Some(&pool), None).unwrap(), Volume { swaps: 3, usdc: 1_003 + 997 + 5, sol: 31, fees_usdc: 5, fees_sol: 1 });
+        assert_eq!(store.volume(None, Some(300)).unwrap(), Volume { swaps: 1, usdc: 50, sol: 1, fees_usdc: 1, fees_sol: 0 });
+
+        // the last event in a slot wins the snapshot
+        let tvl = store.tvl().unwrap();
+        let pool_tvl = tvl.iter().find(|t| t.pool == pool.to_string()).unwrap();
+        assert_eq!((pool_tvl.slot, pool_tvl.usdc_reserve), (4, 0));
+        let other_tvl = tvl.iter().find(|t| t.pool == other_pool.to_string()).unwrap();
+        assert_eq!((other_tvl.slot, other_tvl.usdc_reserve), (5, 5_001));
+        assert_eq!(tvl.len(), 2);
+
+        let history = store.lp_history(&owner).unwrap();
+        assert_eq!(history.iter().map(|a| (a.kind.as_str(), a.slot)).collect::<Vec<_>>(), vec![("deposit", 1), ("collect", 3), ("withdraw", 4)]);
+        assert_eq!((history[1].usdc_fees, history[2].usdc_fees), (3, 5));
+        assert_eq!(store.last_signature().unwrap().as_deref(), Some("e"));
+    }
+
+    #[test]
+    fn reconciles_and_flash_loans_move_the_snapshot() {
+        let mut store = Store::open_in_memory().unwrap();
+        let pool = Pubkey::new_unique();
+        store.insert(&tx("a", 1, 100, vec![swap(pool, true, 1_003, 10, (3, 0), (101_000, 990))])).unwrap();
+
+        let sync = PoolEvent::Reconcile(ReconcileEvent {
+            pool,
+            authority: Pubkey::new_unique(),
+            skim: false,
+            usdc_amount: 500,
+            sol_amount: 0,
+            usdc_reserve: 101_500,
+            sol_reserve: 990,
+        });
+        store.insert(&tx("b", 2, 200, vec![sync])).unwrap();
+        assert_eq!(store.tvl().unwrap()[0].usdc_reserve, 101_500);
+
+        let flash_loan = PoolEvent::FlashLoan(FlashLoanEvent {
+            pool,
+            borrower: Pubkey::new_unique(),
+            usdc_amount: 10_000,
+            sol_amount: 0,
+            usdc_fee: 9,
+            sol_fee: 0,
+            usdc_reserve: 101_500,
+            sol_reserve: 990,
+        });
+        store.insert(&tx("c", 3, 300, vec![flash_loan])).unwrap();
+
+        let tvl = store.tvl().unwrap();
+        assert_eq!((tvl[0].slot, tvl[0].usdc_reserve, tvl[0].sol_reserve), (3, 101_500, 990));
+        // neither is swap volume
+        assert_eq!(store.volume(Some(&pool), None).unwrap().swaps, 1);
+    }
+}
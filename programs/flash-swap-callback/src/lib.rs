@@ -0,0 +1,46 @@
+//! stand-in for a flash_swap borrower in the liquidity-pool tests. it pays `amount` back to
+//! the pool from the caller's account, so a test picks whether the loan is repaid or short.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("AMsCLgVR422xgceyspzTJkJWausUZf9ezwzTbuhAkhUQ");
+
+#[program]
+pub mod flash_swap_callback {
+    use super::*;
+
+    pub fn repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.from.to_account_info(),
+            to: ctx.accounts.to.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Repay<'info> {
+    /// signs the outer flash_swap, its signature carries through the cpi
+    pub payer: Signer<'info>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+        token::token_program = token_program
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+    /// the pool's vault
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>
+}
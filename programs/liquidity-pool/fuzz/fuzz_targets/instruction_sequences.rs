@@ -0,0 +1,102 @@
+//! random deposit / swap / withdraw sequences from a few users against the built program.
+//! after every step the lp positions have to add up to the pool's shares and the vaults
+//! have to cover reserves + fees. a panic inside the program counts as a crash too.
+//!
+//!     anchor build && cargo +nightly fuzz run instruction_sequences
+
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use arbitrary::Arbitrary;
+use common::TestPool;
+use libfuzzer_sys::fuzz_target;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const USERS: usize = 3;
+const MAX_STEPS: usize = 32;
+const USER_FUNDS: u64 = 1 << 60;
+
+#[derive(Arbitrary, Debug)]
+enum Action {
+    /// `sol_amount: None` deposits at the pool ratio so the sequence gets past the imbalance check
+    Deposit { user: u8, usdc_amount: u64, sol_amount: Option<u64> },
+    Swap { user: u8, amount: u64, usdc_in: bool },
+    Withdraw { user: u8 },
+}
+
+fuzz_target!(|actions: Vec<Action>| {
+    let mut pool = TestPool::new();
+    let users: Vec<Keypair> = (0..USERS).map(|_| pool.new_user(USER_FUNDS, USER_FUNDS)).collect();
+
+    for action in actions.iter().take(MAX_STEPS) {
+        let ix = match *action {
+            Action::Deposit { user, usdc_amount, sol_amount } => {
+                let usdc_amount = usdc_amount % USER_FUNDS;
+                let sol_amount = sol_amount.map(|amount| amount % USER_FUNDS).unwrap_or_else(|| {
+                    match pool.pool_state() {
+                        Some(state) if state.total_usdc_deposit > 0 => {
+                            (usdc_amount as u128 * state.total_sol_deposit as u128).div_ceil(state.total_usdc_deposit as u128) as u64
+                        }
+                        _ => usdc_amount,
+                    }
+                });
+                pool.deposit_ix(&user_key(&users, user), usdc_amount, sol_amount)
+            }
+            Action::Swap { user, amount, usdc_in } => pool.swap_ix(&user_key(&users, user), amount % USER_FUNDS, usdc_in),
+            Action::Withdraw { user } => pool.withdraw_ix(&user_key(&users, user)),
+        };
+
+        let signer = &users[action_user(action) as usize % USERS];
+        let logs = match pool.send(ix, &[signer]) {
+            Ok(meta) => meta.logs,
+            Err(failed) => failed.meta.logs,
+        };
+        // rejected instructions are fine, the program aborting on arithmetic is not
+        assert!(
+            !logs.iter().any(|log| log.contains("panicked")),
+            "program panicked on {action:?}\n{}",
+            logs.join("\n")
+        );
+
+        check_invariants(&pool, &users);
+    }
+});
+
+fn action_user(action: &Action) -> u8 {
+    match *action {
+        Action::Deposit { user, .. } | Action::Swap { user, .. } | Action::Withdraw { user } => user,
+    }
+}
+
+fn user_key(users: &[Keypair], user: u8) -> anchor_lang::prelude::Pubkey {
+    users[user as usize % USERS].pubkey()
+}
+
+fn check_invariants(pool: &TestPool, users: &[Keypair]) {
+    let Some(state) = pool.pool_state() else { return };
+
+    let lp_shares: u64 = users
+        .iter()
+        .filter_map(|user| pool.user_state(&user.pubkey()))
+        .map(|position| position.total_shares)
+        .sum();
+    assert_eq!(lp_shares, state.total_shares, "lp positions don't add up to the pool's shares");
+
+    let usdc_vault = pool.token_balance(&pool.pool_usdc_ata);
+    let sol_vault = pool.token_balance(&pool.pool_sol_ata);
+    assert!(
+        usdc_vault as u128 >= state.total_usdc_deposit as u128 + state.fees_collected_usdc as u128,
+        "usdc vault {usdc_vault} below reserves {} + fees {}",
+        state.total_usdc_deposit,
+        state.fees_collected_usdc
+    );
+    assert!(
+        sol_vault as u128 >= state.total_sol_deposit as u128 + state.fees_collected_sol as u128,
+        "sol vault {sol_vault} below reserves {} + fees {}",
+        state.total_sol_deposit,
+        state.fees_collected_sol
+    );
+}
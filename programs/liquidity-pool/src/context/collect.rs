@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}};
+
+use crate::{error::PoolError, events::FeeCollectEvent, state::{Pool, User}};
+
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"lp", pool_pda.key().as_ref(), signer.key().as_ref()],
+        bump
+    )]
+    pub user_pda: Account<'info, User>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = sol_token_program
+    )]
+    pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+/// pays the position the fees it earned since its last collection, its shares stay in the pool
+pub fn process_collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+
+    // the pool signs the payouts, so its borrow has to end before them
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+
+    let (usdc_fees, sol_fees) = ctx.accounts.user_pda.collect_fees(&mut pool_pda)?;
+    require!(usdc_fees > 0 || sol_fees > 0, PoolError::NoFeesToCollect);
+
+    let bump_pool = pool_pda.bump;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    drop(pool_pda);
+
+    let usdc_mint_key = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint_key = ctx.accounts.wrapped_sol_mint.key();
+    let seeds = [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), fee_tier.as_ref(), &[bump_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if usdc_fees > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_usdc_ata.to_account_info(),
+            to: ctx.accounts.user_usdc_ata.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.usdc_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, usdc_fees, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if sol_fees > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_sol_ata.to_account_info(),
+            to: ctx.accounts.user_sol_ata.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.sol_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, sol_fees, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    ctx.accounts.pool_pda.load()?.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(FeeCollectEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        owner: ctx.accounts.signer.key(),
+        usdc_amount: usdc_fees,
+        sol_amount: sol_fees
+    });
+
+    Ok(())
+}
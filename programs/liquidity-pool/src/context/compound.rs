@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{error::PoolError, events::CompoundEvent, state::Pool};
+
+#[derive(Accounts)]
+pub struct CompoundFees<'info> {
+    pub keeper: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    /// where the keeper reward is paid, a side without an account is compounded in full
+    #[account(mut, token::mint = usdc_mint, token::token_program = usdc_token_program)]
+    pub keeper_usdc_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = wrapped_sol_mint, token::token_program = sol_token_program)]
+    pub keeper_sol_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>
+}
+
+/// permissionless, moves the fees every position earned since the last checkpoint into the
+/// reserves after paying the keeper the pool's `compound_reward_bps`
+pub fn process_compound_fees(ctx: Context<CompoundFees>) -> Result<()> {
+
+    // the pool signs the keeper payouts, so its borrow has to end before them
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+
+    let reward_bps = pool_pda.compound_reward_bps;
+    let usdc_bps = if ctx.accounts.keeper_usdc_account.is_some() { reward_bps } else { 0 };
+    let sol_bps = if ctx.accounts.keeper_sol_account.is_some() { reward_bps } else { 0 };
+
+    let ((usdc_amount, sol_amount), (keeper_usdc, keeper_sol)) = pool_pda.compound_fees(usdc_bps, sol_bps)?;
+    pool_pda.update_volatility()?;
+
+    let bump_pool = pool_pda.bump;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    drop(pool_pda);
+
+    let usdc_mint_key = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint_key = ctx.accounts.wrapped_sol_mint.key();
+    let seeds = [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), fee_tier.as_ref(), &[bump_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if let Some(keeper_usdc_account) = ctx.accounts.keeper_usdc_account.as_ref().filter(|_| keeper_usdc > 0) {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_usdc_ata.to_account_info(),
+            to: keeper_usdc_account.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.usdc_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, keeper_usdc, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if let Some(keeper_sol_account) = ctx.accounts.keeper_sol_account.as_ref().filter(|_| keeper_sol > 0) {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_sol_ata.to_account_info(),
+            to: keeper_sol_account.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.sol_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, keeper_sol, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    let pool_pda = ctx.accounts.pool_pda.load()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(CompoundEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        keeper: ctx.accounts.keeper.key(),
+        usdc_amount,
+        sol_amount,
+        keeper_usdc,
+        keeper_sol,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
+
+    Ok(())
+}
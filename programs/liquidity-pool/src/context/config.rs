@@ -0,0 +1,74 @@
+use anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable};
+
+use crate::{error::PoolError, state::{Config, MAX_FEE_BPS, MAX_FEE_TIERS}};
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    /// only the upgrade authority can create the config, otherwise the first caller would
+    /// own the fee tier whitelist
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ PoolError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeTiers<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PoolError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn process_initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.fee_tiers = vec![1, 5, 30, 100];
+    config.bump = ctx.bumps.config;
+
+    msg!("Config initialised by admin {}", ctx.accounts.admin.key().to_string());
+
+    Ok(())
+}
+
+pub fn process_add_fee_tier(ctx: Context<UpdateFeeTiers>, fee_tier: u16) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    require!(fee_tier > 0 && (fee_tier as u64) <= MAX_FEE_BPS, PoolError::InvalidFeeBounds);
+    require!(!config.fee_tiers.contains(&fee_tier), PoolError::DuplicateFeeTier);
+    require!(config.fee_tiers.len() < MAX_FEE_TIERS, PoolError::FeeTierLimit);
+
+    config.fee_tiers.push(fee_tier);
+
+    Ok(())
+}
+
+pub fn process_remove_fee_tier(ctx: Context<UpdateFeeTiers>, fee_tier: u16) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    require!(config.fee_tiers.contains(&fee_tier), PoolError::InvalidFeeTier);
+
+    // existing pools on this tier keep working, only new pools are blocked
+    config.fee_tiers.retain(|tier| *tier != fee_tier);
+
+    Ok(())
+}
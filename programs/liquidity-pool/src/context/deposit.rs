@@ -1,52 +1,59 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{transfer_checked, TransferChecked}, token_interface::{Mint, TokenAccount, TokenInterface}};
-use crate::{error::{DepositError, PoolError}, state::*};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}};
+use crate::{context::update_rewards, error::{DepositError, PoolError}, events::DepositEvent, extensions::{amount_received, validate_mint}, native_sol::{require_native_mint, unwrap_sol, wrap_sol}, state::*};
 
 #[derive(Accounts)]
+#[instruction(usdc_amount: u64, wrapped_sol_amount: u64, fee_tier: u16, native_sol: bool)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
-    #[account(mut)]
+    #[account(mut, mint::token_program = usdc_token_program)]
     pub usdc_mint: InterfaceAccount<'info, Mint>,
-    #[account(mut)]
+    #[account(mut, mint::token_program = sol_token_program)]
     pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut, 
         associated_token::mint = usdc_mint, 
         associated_token::authority = signer, 
-        associated_token::token_program = token_program
+        associated_token::token_program = usdc_token_program
     )]
     pub user_usdc_ata: InterfaceAccount<'info,TokenAccount>,
     #[account(
-        mut, 
+        init_if_needed, 
+        payer = signer, 
         associated_token::mint = wrapped_sol_mint, 
         associated_token::authority = signer, 
-        associated_token::token_program = token_program
+        associated_token::token_program = sol_token_program
     )]
     pub user_sol_ata: InterfaceAccount<'info,TokenAccount>,
     #[account(
         init_if_needed, 
         payer = signer, 
-        space = 8 + User::INIT_SPACE, 
-        seeds = [b"lp", signer.key().as_ref()],
+        space = 8 + std::mem::size_of::<Pool>(), 
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), fee_tier.to_le_bytes().as_ref()],
         bump
     )]
-    pub user_pda: Account<'info, User>,
+    pub pool_pda: AccountLoader<'info, Pool>,
     #[account(
         init_if_needed, 
         payer = signer, 
-        space = 8 + Pool::INIT_SPACE, 
-        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref()],
+        space = 8 + User::INIT_SPACE, 
+        seeds = [b"lp", pool_pda.key().as_ref(), signer.key().as_ref()],
         bump
     )]
-    pub pool_pda: Account<'info, Pool>,
+    pub user_pda: Account<'info, User>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
 
     #[account(
         init_if_needed, 
         payer = signer, 
         associated_token::mint = usdc_mint, 
         associated_token::authority = pool_pda, 
-        associated_token::token_program = token_program
+        associated_token::token_program = usdc_token_program
     )]
     pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
 
@@ -55,40 +62,84 @@ pub struct Deposit<'info> {
         payer = signer, 
         associated_token::mint = wrapped_sol_mint, 
         associated_token::authority = pool_pda, 
-        associated_token::token_program = token_program
+        associated_token::token_program = sol_token_program
     )]
     pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    /// required once the pool has rewards
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>
 }
 
 
-pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<()> {
+pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amount: u64, fee_tier: u16, native_sol: bool) -> Result<()> {
 
     require!( (usdc_amount > 0 || wrapped_sol_amount > 0), DepositError::ZeroAmountError );
-    let pool_pda =&mut ctx.accounts.pool_pda;
+    let pool_key = ctx.accounts.pool_pda.key();
     let user_pda = &mut ctx.accounts.user_pda;
+    // already implied by the seeds, kept so the account says which pool it is in
+    user_pda.pool = pool_key;
+
+    // a pool created by init_if_needed has no discriminator until the instruction exits
+    let is_pool_initialise = ctx.accounts.pool_pda.load().is_ok_and(|pool| pool.is_initialise != 0);
+    // the pool never signs a transfer here, so it can stay borrowed for the whole instruction
+    let mut pool_pda = if is_pool_initialise {
+        ctx.accounts.pool_pda.load_mut()?
+    } else {
+        ctx.accounts.pool_pda.load_init()?
+    };
+
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    let now = Clock::get()?.unix_timestamp;
+    update_rewards(&pool_pda, ctx.accounts.reward_vault.as_mut(), user_pda, now)?;
+    // uncollected fees stay with the position while its shares change
+    let (pending_usdc, pending_sol) = user_pda.collect_fees(&mut pool_pda)?;
+
+    if native_sol {
+        require_native_mint(&ctx.accounts.wrapped_sol_mint.key())?;
+        wrap_sol(
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.user_sol_ata.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sol_token_program.to_account_info(),
+            wrapped_sol_amount
+        )?;
+    }
+
+    // token-2022 transfer fees mean the vault can receive less than what is sent
+    let usdc_received = amount_received(&ctx.accounts.usdc_mint.to_account_info(), usdc_amount)?;
+    let sol_received = amount_received(&ctx.accounts.wrapped_sol_mint.to_account_info(), wrapped_sol_amount)?;
 
-    let is_pool_initialise = pool_pda.is_initialise;
+    let shares_before = pool_pda.total_shares;
 
     if !is_pool_initialise {
+        require!(ctx.accounts.config.fee_tiers.contains(&fee_tier), PoolError::InvalidFeeTier);
+        validate_mint(&ctx.accounts.usdc_mint.to_account_info())?;
+        validate_mint(&ctx.accounts.wrapped_sol_mint.to_account_info())?;
+
         user_pda.owner = ctx.accounts.signer.key();
-        user_pda.sol_deposit = wrapped_sol_amount;
-        user_pda.usdc_deposit = usdc_amount; 
+        user_pda.version = USER_VERSION;
+        user_pda.sol_deposit = sol_received;
+        user_pda.usdc_deposit = usdc_received;
 
-        pool_pda.total_usdc_deposit = usdc_amount;
-        pool_pda.total_sol_deposit = wrapped_sol_amount;
         pool_pda.bump = ctx.bumps.pool_pda;
-        pool_pda.is_initialise = true;
+        pool_pda.is_initialise = 1;
         pool_pda.fees_collected_usdc = 0;
-        pool_pda.liquidity_fees = 30;
-
-        let product = (usdc_amount as u128).checked_mul(wrapped_sol_amount as u128).ok_or(PoolError::MathOverFlow)?;
-        let user_shares = product.isqrt() as u64;
+        pool_pda.fee_tier = fee_tier;
+        pool_pda.liquidity_fees = fee_tier as u64;
+        pool_pda.authority = ctx.accounts.signer.key();
+        pool_pda.min_fee_bps = fee_tier as u64;
+        pool_pda.max_fee_bps = fee_tier as u64;
+        pool_pda.version = POOL_VERSION;
 
-        user_pda.total_shares = user_shares;
-        pool_pda.total_shares = user_shares;
+        user_pda.total_shares = pool_pda.add_liquidity(usdc_received, sol_received)?;
 
         let usdc_cpi_accounts = TransferChecked {
             from: ctx.accounts.user_usdc_ata.to_account_info(), 
@@ -97,7 +148,7 @@ pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amou
             authority: ctx.accounts.signer.to_account_info()
         };
         let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.usdc_token_program.to_account_info(),
              usdc_cpi_accounts);
 
         transfer_checked(cpi_ctx, usdc_amount, ctx.accounts.usdc_mint.decimals)?;
@@ -110,7 +161,7 @@ pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amou
         };
 
         let sol_cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(), 
+            ctx.accounts.sol_token_program.to_account_info(), 
                     sol_cpi_accounts);
 
         transfer_checked(sol_cpi_ctx, wrapped_sol_amount, ctx.accounts.wrapped_sol_mint.decimals)?;
@@ -118,31 +169,7 @@ pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amou
         msg!("Pool is initiliased by signer {}", ctx.accounts.signer.key().to_string());
     }else { 
 
-        let deposit_ratio = (usdc_amount as u128).checked_mul(100_000).ok_or(DepositError::MultiplyError)?.checked_div(wrapped_sol_amount as u128).ok_or(DepositError::DivisionError)? as u64;
-        let pool_ratio = (pool_pda.total_usdc_deposit as u128).checked_mul(100_000).ok_or(DepositError::MultiplyError)?.checked_div(pool_pda.total_sol_deposit as u128).ok_or(DepositError::DivisionError)? as u64;
-        
-
-        let diff = if deposit_ratio > pool_ratio {
-            deposit_ratio - pool_ratio
-        } else {
-            pool_ratio - deposit_ratio
-        };
-
-        require!(diff <= pool_ratio / 100, PoolError::ImbalancedDeposit);
-
-        let usdc_shares = (usdc_amount as u128)
-                            .checked_mul(pool_pda.total_shares as u128)
-                            .ok_or(DepositError::MultiplyError)?
-                            .checked_div(pool_pda.total_usdc_deposit as u128)
-                            .ok_or(DepositError::DivisionError)?;
-        let sol_shares = (wrapped_sol_amount as u128)
-                            .checked_mul(pool_pda.total_shares as u128)
-                            .ok_or(DepositError::MultiplyError)?
-                            .checked_div(pool_pda.total_sol_deposit as u128)
-                            .ok_or(DepositError::DivisionError)?;
-        
-         let new_shares = usdc_shares.min(sol_shares) as u64;
-         require!(new_shares > 0, PoolError::ZeroShares);
+        let new_shares = pool_pda.add_liquidity(usdc_received, sol_received)?;
 
 
         let usdc_cpi_accounts = TransferChecked {
@@ -152,7 +179,7 @@ pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amou
             authority: ctx.accounts.signer.to_account_info()
         };
         let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.usdc_token_program.to_account_info(),
              usdc_cpi_accounts);
 
         transfer_checked(cpi_ctx, usdc_amount, ctx.accounts.usdc_mint.decimals)?;
@@ -165,22 +192,45 @@ pub fn process_deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amou
         };
 
         let sol_cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(), 
+            ctx.accounts.sol_token_program.to_account_info(), 
                     sol_cpi_accounts);
 
         transfer_checked(sol_cpi_ctx, wrapped_sol_amount, ctx.accounts.wrapped_sol_mint.decimals)?;
 
       
 
-        user_pda.sol_deposit += wrapped_sol_amount;
-        user_pda.usdc_deposit += usdc_amount;
+        user_pda.sol_deposit += sol_received;
+        user_pda.usdc_deposit += usdc_received;
         user_pda.total_shares += new_shares;
         user_pda.owner = ctx.accounts.signer.key();
+        user_pda.version = USER_VERSION;
+    }
+
+    user_pda.carry_fees(&mut pool_pda, pending_usdc, pending_sol)?;
+    // a locked position's new shares are boosted too
+    user_pda.refresh_boost(&mut pool_pda, now)?;
 
-        pool_pda.total_shares += new_shares;
-        pool_pda.total_sol_deposit += wrapped_sol_amount;
-        pool_pda.total_usdc_deposit += usdc_amount;
+    if native_sol {
+        unwrap_sol(
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.user_sol_ata.to_account_info(),
+            ctx.accounts.sol_token_program.to_account_info()
+        )?;
     }
 
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(DepositEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        owner: ctx.accounts.signer.key(),
+        usdc_amount: usdc_received,
+        sol_amount: sol_received,
+        shares: pool_pda.total_shares - shares_before,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
+
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{error::PoolError, state::{Config, Pool, MAX_COMPOUND_REWARD_BPS, MAX_DYNAMIC_FEE_BPS}};
+
+/// only the config admin can move a pool's fee away from its tier
+#[derive(Accounts)]
+pub struct SetDynamicFee<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PoolError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+}
+
+#[derive(Accounts)]
+pub struct SetCompoundReward<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ PoolError::Unauthorized
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+}
+
+pub fn process_set_dynamic_fee(ctx: Context<SetDynamicFee>, enabled: bool, min_fee_bps: u64, max_fee_bps: u64) -> Result<()> {
+
+    require!(min_fee_bps <= max_fee_bps && max_fee_bps <= MAX_DYNAMIC_FEE_BPS, PoolError::InvalidFeeBounds);
+
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+
+    pool_pda.dynamic_fee_enabled = enabled as u8;
+    pool_pda.min_fee_bps = min_fee_bps;
+    pool_pda.max_fee_bps = max_fee_bps;
+    pool_pda.reference_price = 0;
+    pool_pda.volatility_bps = 0;
+    pool_pda.last_update_ts = Clock::get()?.unix_timestamp;
+
+    msg!("Dynamic fee set to {} with bounds {}-{} bps", enabled, min_fee_bps, max_fee_bps);
+
+    Ok(())
+}
+
+pub fn process_set_compound_reward(ctx: Context<SetCompoundReward>, reward_bps: u64) -> Result<()> {
+
+    require!(reward_bps <= MAX_COMPOUND_REWARD_BPS, PoolError::InvalidFeeBounds);
+
+    ctx.accounts.pool_pda.load_mut()?.compound_reward_bps = reward_bps;
+
+    msg!("Compound reward set to {} bps", reward_bps);
+
+    Ok(())
+}
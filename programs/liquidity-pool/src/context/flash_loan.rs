@@ -0,0 +1,220 @@
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_lang::solana_program::{
+    instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{error::{DepositError, PoolError}, events::FlashLoanEvent, extensions::amount_to_send, state::{Pool, FLASH_LOAN_FEE_BPS}};
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::token_program = usdc_token_program
+    )]
+    pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = wrapped_sol_mint,
+        token::token_program = sol_token_program
+    )]
+    pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: address is checked against the instructions sysvar id
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn flash_loan_fee(amount: u64) -> Result<u64> {
+    // rounded up so tiny loans still pay something
+    let fee = (amount as u128)
+        .checked_mul(FLASH_LOAN_FEE_BPS as u128)
+        .ok_or(PoolError::MathOverFlow)?
+        .checked_add(9_999)
+        .ok_or(PoolError::MathOverFlow)?
+        / 10_000;
+    Ok(fee as u64)
+}
+
+pub fn process_flash_borrow(ctx: Context<FlashLoan>, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<()> {
+
+    require!((usdc_amount > 0 || wrapped_sol_amount > 0), DepositError::ZeroAmountError);
+    // introspection only sees top level instructions
+    require!(get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT, PoolError::CpiNotAllowed);
+
+    let pool_key = ctx.accounts.pool_pda.key();
+    let usdc_mint = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint = ctx.accounts.wrapped_sol_mint.key();
+    // the pool signs the transfers below, so its borrow has to end before them
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    let bump_pool = pool_pda.bump;
+
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    require!(usdc_amount <= pool_pda.total_usdc_deposit && wrapped_sol_amount <= pool_pda.total_sol_deposit, PoolError::InsufficientLiquidity);
+
+    // a matching repay for this pool has to come later in the same transaction
+    let instructions = ctx.accounts.instructions.to_account_info();
+    let current_index = load_current_index_checked(&instructions)? as usize;
+    let mut index = current_index + 1;
+    let mut repay_found = false;
+
+    while let Ok(ix) = load_instruction_at_checked(index, &instructions) {
+        if ix.program_id == crate::ID
+            && ix.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+            && ix.accounts.iter().any(|meta| meta.pubkey == pool_key)
+        {
+            repay_found = true;
+            break;
+        }
+        index += 1;
+    }
+
+    require!(repay_found, PoolError::MissingFlashRepay);
+
+    pool_pda.flash_loan_active = 1;
+    pool_pda.flash_borrowed_usdc = usdc_amount;
+    pool_pda.flash_borrowed_sol = wrapped_sol_amount;
+    drop(pool_pda);
+
+    let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), fee_tier.as_ref(), &[bump_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if usdc_amount > 0 {
+        let usdc_cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_usdc_ata.to_account_info(),
+            to: ctx.accounts.user_usdc_ata.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+
+        let usdc_ctx = CpiContext::new_with_signer(
+            ctx.accounts.usdc_token_program.to_account_info(),
+            usdc_cpi_accounts,
+            signer_seeds
+        );
+        transfer_checked(usdc_ctx, usdc_amount, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if wrapped_sol_amount > 0 {
+        let sol_cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_sol_ata.to_account_info(),
+            to: ctx.accounts.user_sol_ata.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+
+        let sol_ctx = CpiContext::new_with_signer(
+            ctx.accounts.sol_token_program.to_account_info(),
+            sol_cpi_accounts,
+            signer_seeds
+        );
+        transfer_checked(sol_ctx, wrapped_sol_amount, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    msg!("Flash loan of {} usdc and {} sol to {}", usdc_amount, wrapped_sol_amount, ctx.accounts.signer.key().to_string());
+
+    Ok(())
+}
+
+pub fn process_flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+
+    require!(pool_pda.flash_loan_active != 0, PoolError::FlashLoanNotActive);
+
+    let usdc_amount = pool_pda.flash_borrowed_usdc;
+    let wrapped_sol_amount = pool_pda.flash_borrowed_sol;
+    let usdc_fee = flash_loan_fee(usdc_amount)?;
+    let sol_fee = flash_loan_fee(wrapped_sol_amount)?;
+
+    // fees go to the lp buckets exactly like swap fees
+    pool_pda.add_fees(usdc_fee, sol_fee)?;
+    pool_pda.flash_loan_active = 0;
+    pool_pda.flash_borrowed_usdc = 0;
+    pool_pda.flash_borrowed_sol = 0;
+    drop(pool_pda);
+
+    if usdc_amount > 0 {
+        let usdc_cpi_accounts = TransferChecked {
+            from: ctx.accounts.user_usdc_ata.to_account_info(),
+            to: ctx.accounts.pool_usdc_ata.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.signer.to_account_info()
+        };
+
+        let usdc_ctx = CpiContext::new(
+            ctx.accounts.usdc_token_program.to_account_info(),
+            usdc_cpi_accounts
+        );
+        let usdc_owed = usdc_amount.checked_add(usdc_fee).ok_or(DepositError::OverFlow)?;
+        let usdc_to_send = amount_to_send(&ctx.accounts.usdc_mint.to_account_info(), usdc_owed)?;
+        transfer_checked(usdc_ctx, usdc_to_send, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if wrapped_sol_amount > 0 {
+        let sol_cpi_accounts = TransferChecked {
+            from: ctx.accounts.user_sol_ata.to_account_info(),
+            to: ctx.accounts.pool_sol_ata.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.signer.to_account_info()
+        };
+
+        let sol_ctx = CpiContext::new(
+            ctx.accounts.sol_token_program.to_account_info(),
+            sol_cpi_accounts
+        );
+        let sol_owed = wrapped_sol_amount.checked_add(sol_fee).ok_or(DepositError::OverFlow)?;
+        let sol_to_send = amount_to_send(&ctx.accounts.wrapped_sol_mint.to_account_info(), sol_owed)?;
+        transfer_checked(sol_ctx, sol_to_send, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    let pool_pda = ctx.accounts.pool_pda.load()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(FlashLoanEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        borrower: ctx.accounts.signer.key(),
+        usdc_amount,
+        sol_amount: wrapped_sol_amount,
+        usdc_fee,
+        sol_fee,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
+
+    msg!("Flash loan repaid with fees {} usdc and {} sol", usdc_fee, sol_fee);
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::{AccountMeta, Instruction}, program::invoke};
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{error::{DepositError, PoolError}, events::SwapEvent, state::Pool};
+
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::token_program = usdc_token_program
+    )]
+    pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = wrapped_sol_mint,
+        token::token_program = sol_token_program
+    )]
+    pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: any program chosen by the caller, it only gets the remaining accounts
+    #[account(executable)]
+    pub callback_program: UncheckedAccount<'info>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+}
+
+/// amount that actually reached the vault, minus the fee on it
+fn net_input(balance_after: u64, balance_before: u64, amount_out: u64, fee_bps: u64) -> Result<(u64, u64)> {
+    let expected = balance_before.checked_sub(amount_out).ok_or(DepositError::Underflow)?;
+    let amount_in = balance_after.saturating_sub(expected);
+    let fee = ((amount_in as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(PoolError::MathOverFlow)?
+        .checked_add(9_999)
+        .ok_or(PoolError::MathOverFlow)?
+        / 10_000) as u64;
+    Ok((amount_in - fee.min(amount_in), fee.min(amount_in)))
+}
+
+pub fn process_flash_swap<'info>(ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>, usdc_out: u64, wrapped_sol_out: u64, callback_data: Vec<u8>) -> Result<()> {
+
+    require!((usdc_out > 0 || wrapped_sol_out > 0), DepositError::ZeroAmountError);
+
+    let usdc_mint = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint = ctx.accounts.wrapped_sol_mint.key();
+
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    let bump_pool = pool_pda.bump;
+
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    require!(usdc_out < pool_pda.total_usdc_deposit && wrapped_sol_out < pool_pda.total_sol_deposit, PoolError::InsufficientLiquidity);
+
+    let fee_bps = pool_pda.swap_fee_bps(Clock::get()?.unix_timestamp)?;
+
+    // zero-copy writes land in the account data directly, so a re-entrant call
+    // sees the lock. the borrow has to end before the pool signs or the callback runs.
+    pool_pda.flash_loan_active = 1;
+    drop(pool_pda);
+
+    let usdc_before = ctx.accounts.pool_usdc_ata.amount;
+    let sol_before = ctx.accounts.pool_sol_ata.amount;
+
+    let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), fee_tier.as_ref(), &[bump_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if usdc_out > 0 {
+        let usdc_cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_usdc_ata.to_account_info(),
+            to: ctx.accounts.user_usdc_ata.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+
+        let usdc_ctx = CpiContext::new_with_signer(
+            ctx.accounts.usdc_token_program.to_account_info(),
+            usdc_cpi_accounts,
+            signer_seeds
+        );
+        transfer_checked(usdc_ctx, usdc_out, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if wrapped_sol_out > 0 {
+        let sol_cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_sol_ata.to_account_info(),
+            to: ctx.accounts.user_sol_ata.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+
+        let sol_ctx = CpiContext::new_with_signer(
+            ctx.accounts.sol_token_program.to_account_info(),
+            sol_cpi_accounts,
+            signer_seeds
+        );
+        transfer_checked(sol_ctx, wrapped_sol_out, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    let callback_accounts = ctx.remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+
+    let callback_ix = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts: callback_accounts,
+        data: callback_data,
+    };
+
+    invoke(&callback_ix, ctx.remaining_accounts)?;
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+
+    let (usdc_in, usdc_fee) = net_input(ctx.accounts.pool_usdc_ata.amount, usdc_before, usdc_out, fee_bps)?;
+    let (sol_in, sol_fee) = net_input(ctx.accounts.pool_sol_ata.amount, sol_before, wrapped_sol_out, fee_bps)?;
+
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+
+    let new_usdc = pool_pda.total_usdc_deposit
+        .checked_add(usdc_in).ok_or(DepositError::OverFlow)?
+        .checked_sub(usdc_out).ok_or(DepositError::Underflow)?;
+    let new_sol = pool_pda.total_sol_deposit
+        .checked_add(sol_in).ok_or(DepositError::OverFlow)?
+        .checked_sub(wrapped_sol_out).ok_or(DepositError::Underflow)?;
+
+    let constant_product = pool_pda.total_usdc_deposit as u128 * pool_pda.total_sol_deposit as u128;
+    let new_product = new_usdc as u128 * new_sol as u128;
+
+    require!(new_product >= constant_product, PoolError::InvariantViolated);
+
+    pool_pda.total_usdc_deposit = new_usdc;
+    pool_pda.total_sol_deposit = new_sol;
+    pool_pda.add_fees(usdc_fee, sol_fee)?;
+    pool_pda.flash_loan_active = 0;
+    pool_pda.update_volatility()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    // borrowed sol is paid for in usdc, anything else is reported as a sol in swap
+    let usdc_side = wrapped_sol_out > 0;
+    let (amount_in, amount_out) = if usdc_side {
+        (usdc_in + usdc_fee, wrapped_sol_out)
+    } else {
+        (sol_in + sol_fee, usdc_out)
+    };
+    emit!(SwapEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        trader: ctx.accounts.signer.key(),
+        usdc_in: usdc_side,
+        amount_in,
+        amount_out,
+        usdc_fee,
+        sol_fee,
+        usdc_reserve: new_usdc,
+        sol_reserve: new_sol
+    });
+
+    msg!("Flash swap paid {} usdc and {} sol for {} usdc and {} sol", usdc_in, sol_in, usdc_out, wrapped_sol_out);
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{context::update_rewards, error::PoolError, state::{lock_boost_bps, Pool, RewardVault, User}};
+
+#[derive(Accounts)]
+pub struct LockPosition<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"lp", pool_pda.key().as_ref(), signer.key().as_ref()],
+        bump
+    )]
+    pub user_pda: Account<'info, User>,
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    /// required once the pool has rewards
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>
+}
+
+#[derive(Accounts)]
+pub struct ExpireLock<'info> {
+    #[account(
+        mut,
+        seeds = [b"lp", pool_pda.key().as_ref(), user_pda.owner.as_ref()],
+        bump
+    )]
+    pub user_pda: Account<'info, User>,
+    #[account(mut)]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    /// required once the pool has rewards
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>
+}
+
+/// locks the whole position for `duration` seconds from now in exchange for a reward boost
+/// that grows with the duration. a lock can be renewed but never end earlier.
+pub fn process_lock_position(ctx: Context<LockPosition>, duration: i64) -> Result<()> {
+
+    let boost_bps = lock_boost_bps(duration)?;
+    let now = Clock::get()?.unix_timestamp;
+    let locked_until = now.checked_add(duration).ok_or(PoolError::MathOverFlow)?;
+
+    let user_pda = &mut ctx.accounts.user_pda;
+    require!(user_pda.total_shares > 0, PoolError::ZeroShares);
+    require!(locked_until >= user_pda.locked_until, PoolError::LockShortened);
+
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+
+    // rewards so far are paid at the old boost
+    update_rewards(&pool_pda, ctx.accounts.reward_vault.as_mut(), user_pda, now)?;
+
+    user_pda.locked_until = locked_until;
+    user_pda.lock_boost_bps = boost_bps;
+    user_pda.refresh_boost(&mut pool_pda, now)?;
+
+    msg!("Position locked until {} with a {} bps boost", locked_until, boost_bps);
+
+    Ok(())
+}
+
+/// drops the boost of a lock that has ended. anyone can call it, so an owner who never touches
+/// the position again can't keep diluting the other positions' rewards.
+pub fn process_expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+
+    let now = Clock::get()?.unix_timestamp;
+    let user_pda = &mut ctx.accounts.user_pda;
+    require!(user_pda.boost_shares > 0 && !user_pda.is_locked(now), PoolError::LockNotExpired);
+
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+
+    // the boost is paid up to now, after that only the shares earn
+    update_rewards(&pool_pda, ctx.accounts.reward_vault.as_mut(), user_pda, now)?;
+
+    user_pda.lock_boost_bps = 0;
+    user_pda.refresh_boost(&mut pool_pda, now)?;
+
+    msg!("Lock of {} expired", user_pda.owner);
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked}};
+
+use crate::{error::PoolError, state::{Config, LegacyPool, LegacyUser, Pool, User, LEGACY_POOL_LEN, LEGACY_USER_LEN, USER_VERSION}};
+
+/// pools from before fee tiers live at [b"pool", usdc, sol], which no handler derives.
+/// their state and vaults move to a new pool on a whitelisted tier.
+#[derive(Accounts)]
+#[instruction(fee_tier: u16)]
+pub struct MigrateLegacyPool<'info> {
+    /// legacy pools have no authority, the config admin becomes it
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PoolError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: legacy layouts don't deserialize as `Pool`, the discriminator is checked before reading it
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref()],
+        bump
+    )]
+    pub legacy_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = legacy_pool,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub legacy_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = legacy_pool,
+        associated_token::token_program = sol_token_program
+    )]
+    pub legacy_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Pool>(),
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), fee_tier.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    /// baseline positions don't say which pool they are in, the config admin vouches for it
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ PoolError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    /// has to be a pool migrate_legacy_pool produced, only those owe baseline shares
+    #[account(mut)]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    /// CHECK: gets the baseline account's rent back, checked against the position's owner
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: the baseline layout doesn't deserialize as `User`, the discriminator is checked before reading it
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"lp", owner.key().as_ref()],
+        bump
+    )]
+    pub legacy_user: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + User::INIT_SPACE,
+        seeds = [b"lp", pool_pda.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_pda: Account<'info, User>,
+    pub system_program: Program<'info, System>
+}
+
+/// closes `account` the way anchor's `close` constraint does it
+fn close_program_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    **destination.try_borrow_mut_lamports()? = destination.lamports().checked_add(account.lamports()).ok_or(PoolError::MathOverFlow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+/// moves a pool from before fee tiers to `fee_tier`, which also becomes its swap fee.
+/// its vaults are emptied into the new pool's and closed along with the old account.
+/// positions then go through migrate_position with the new pool, which owes them the legacy shares.
+pub fn process_migrate_legacy_pool(ctx: Context<MigrateLegacyPool>, fee_tier: u16) -> Result<()> {
+
+    require!(ctx.accounts.config.fee_tiers.contains(&fee_tier), PoolError::InvalidFeeTier);
+
+    let legacy = {
+        let data = ctx.accounts.legacy_pool.try_borrow_data()?;
+        require!(data.len() >= 8 && &data[..8] == Pool::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        require!(data.len() == LEGACY_POOL_LEN, PoolError::AlreadyMigrated);
+        LegacyPool::deserialize(&mut &data[8..])?
+    };
+
+    let mut pool = Pool::from_legacy(legacy)?;
+    pool.bump = ctx.bumps.pool_pda;
+    pool.fee_tier = fee_tier;
+    pool.liquidity_fees = fee_tier as u64;
+    pool.min_fee_bps = fee_tier as u64;
+    pool.max_fee_bps = fee_tier as u64;
+    pool.authority = ctx.accounts.admin.key();
+
+    let usdc_mint_key = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint_key = ctx.accounts.wrapped_sol_mint.key();
+    let seeds = [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), &[ctx.bumps.legacy_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    let vaults = [
+        (&ctx.accounts.legacy_usdc_ata, &ctx.accounts.pool_usdc_ata, &ctx.accounts.usdc_mint, &ctx.accounts.usdc_token_program),
+        (&ctx.accounts.legacy_sol_ata, &ctx.accounts.pool_sol_ata, &ctx.accounts.wrapped_sol_mint, &ctx.accounts.sol_token_program),
+    ];
+    for (legacy_ata, pool_ata, mint, token_program) in vaults {
+        let cpi_accounts = TransferChecked {
+            from: legacy_ata.to_account_info(),
+            to: pool_ata.to_account_info(),
+            mint: mint.to_account_info(),
+            authority: ctx.accounts.legacy_pool.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, legacy_ata.amount, mint.decimals)?;
+
+        let cpi_accounts = CloseAccount {
+            account: legacy_ata.to_account_info(),
+            destination: ctx.accounts.admin.to_account_info(),
+            authority: ctx.accounts.legacy_pool.to_account_info()
+        };
+        close_account(CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds))?;
+    }
+
+    close_program_account(&ctx.accounts.legacy_pool.to_account_info(), &ctx.accounts.admin.to_account_info())?;
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    pool.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+    // the discriminator is only written when the instruction exits, so `load` can't be used here
+    *ctx.accounts.pool_pda.load_init()? = pool;
+
+    msg!("Legacy pool migrated to fee tier {}", fee_tier);
+
+    Ok(())
+}
+
+/// moves a baseline position at [b"lp", owner] to its migrated pool. the pool's legacy shares
+/// shrink by the position's, so no pool hands out more than the baseline pool held. has to run
+/// before the owner deposits into the pool again, that opens the new position first.
+pub fn process_migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+
+    let legacy = {
+        let data = ctx.accounts.legacy_user.try_borrow_data()?;
+        require!(data.len() >= 8 && &data[..8] == User::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+        require!(data.len() == LEGACY_USER_LEN, PoolError::AlreadyMigrated);
+        LegacyUser::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(legacy.owner, ctx.accounts.owner.key(), PoolError::Unauthorized);
+
+    let mut pool = ctx.accounts.pool_pda.load_mut()?;
+    pool.legacy_shares = pool.legacy_shares.checked_sub(legacy.total_shares).ok_or(PoolError::LegacySharesExceeded)?;
+
+    // zero checkpoints, the baseline fees and every reward since the vault opened counted these shares
+    let user_pda = &mut ctx.accounts.user_pda;
+    user_pda.owner = legacy.owner;
+    user_pda.usdc_deposit = legacy.usdc_deposit;
+    user_pda.sol_deposit = legacy.sol_deposit;
+    user_pda.total_shares = legacy.total_shares;
+    user_pda.version = USER_VERSION;
+    user_pda.pool = ctx.accounts.pool_pda.key();
+
+    close_program_account(&ctx.accounts.legacy_user.to_account_info(), &ctx.accounts.owner.to_account_info())?;
+
+    msg!("Position of {} migrated", legacy.owner);
+
+    Ok(())
+}
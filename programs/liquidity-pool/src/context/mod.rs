@@ -1,7 +1,31 @@
 mod deposit;
 mod swap; 
 mod withdraw;
+mod fee_config;
+mod config;
+mod flash_loan;
+mod flash_swap;
+mod swap_route;
+mod reconcile;
+mod migrate;
+mod rewards;
+mod lock;
+mod compound;
+mod collect;
+mod position;
 
 pub use withdraw::*;
 pub use swap::*;
-pub use deposit::*;
\ No newline at end of file
+pub use deposit::*;
+pub use fee_config::*;
+pub use config::*;
+pub use flash_loan::*;
+pub use flash_swap::*;
+pub use swap_route::*;
+pub use reconcile::*;
+pub use migrate::*;
+pub use rewards::*;
+pub use lock::*;
+pub use compound::*;
+pub use collect::*;
+pub use position::*;
\ No newline at end of file
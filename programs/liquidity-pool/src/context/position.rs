@@ -0,0 +1,541 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::spl_token_2022::instruction::AuthorityType,
+    token_interface::{burn, close_account, mint_to, set_authority, transfer_checked, Burn, CloseAccount, Mint, MintTo, SetAuthority, TokenAccount, TokenInterface, TransferChecked}
+};
+
+use crate::{
+    context::update_rewards,
+    error::{DepositError, PoolError},
+    events::{DepositEvent, FeeCollectEvent, RewardClaimEvent, WithdrawEvent},
+    extensions::amount_received,
+    state::{Pool, RewardVault, User, USER_VERSION}
+};
+
+// nft positions live at [b"position", pool, position_mint] instead of [b"lp", pool, owner]. whoever
+// holds the single token of the mint owns the position, its checkpoints move with it.
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = sol_token_program
+    )]
+    pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    /// fresh keypair, its mint authority is revoked once the single token is minted
+    #[account(
+        init,
+        payer = signer,
+        mint::decimals = 0,
+        mint::authority = signer,
+        mint::token_program = position_token_program
+    )]
+    pub position_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + User::INIT_SPACE,
+        seeds = [b"position", pool_pda.key().as_ref(), position_mint.key().as_ref()],
+        bump
+    )]
+    pub position_pda: Account<'info, User>,
+    #[account(
+        init,
+        payer = signer,
+        associated_token::mint = position_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = position_token_program
+    )]
+    pub position_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub position_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    /// required once the pool has rewards
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(mut, mint::token_program = position_token_program)]
+    pub position_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"position", pool_pda.key().as_ref(), position_mint.key().as_ref()],
+        bump
+    )]
+    pub position_pda: Account<'info, User>,
+    /// any token account of the holder, it is closed with the position
+    #[account(
+        mut,
+        token::mint = position_mint,
+        token::authority = signer,
+        token::token_program = position_token_program,
+        constraint = position_token_account.amount == 1 @ PoolError::NotPositionHolder
+    )]
+    pub position_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = sol_token_program
+    )]
+    pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub position_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    /// required once the pool has rewards
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>
+}
+
+#[derive(Accounts)]
+pub struct CollectPositionFees<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(mint::token_program = position_token_program)]
+    pub position_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"position", pool_pda.key().as_ref(), position_mint.key().as_ref()],
+        bump
+    )]
+    pub position_pda: Account<'info, User>,
+    #[account(
+        token::mint = position_mint,
+        token::authority = signer,
+        token::token_program = position_token_program,
+        constraint = position_token_account.amount == 1 @ PoolError::NotPositionHolder
+    )]
+    pub position_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = sol_token_program
+    )]
+    pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub position_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct ClaimPositionRewards<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(mint::token_program = position_token_program)]
+    pub position_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"position", pool_pda.key().as_ref(), position_mint.key().as_ref()],
+        bump
+    )]
+    pub position_pda: Account<'info, User>,
+    #[account(
+        token::mint = position_mint,
+        token::authority = signer,
+        token::token_program = position_token_program,
+        constraint = position_token_account.amount == 1 @ PoolError::NotPositionHolder
+    )]
+    pub position_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump,
+        has_one = reward_mint
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+    #[account(mint::token_program = reward_token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = reward_vault,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = reward_token_program
+    )]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+    pub position_token_program: Interface<'info, TokenInterface>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+/// deposits into an existing pool as a new nft position and mints its token to the signer
+pub fn process_open_position(ctx: Context<OpenPosition>, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<()> {
+
+    require!(usdc_amount > 0 || wrapped_sol_amount > 0, DepositError::ZeroAmountError);
+
+    // the pool never signs a transfer here, so it can stay borrowed for the whole instruction
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    let now = Clock::get()?.unix_timestamp;
+
+    // starts the reward and fee checkpoints at the pool's current growth
+    let position_pda = &mut ctx.accounts.position_pda;
+    update_rewards(&pool_pda, ctx.accounts.reward_vault.as_mut(), position_pda, now)?;
+    position_pda.collect_fees(&mut pool_pda)?;
+
+    // token-2022 transfer fees mean the vault can receive less than what is sent
+    let usdc_received = amount_received(&ctx.accounts.usdc_mint.to_account_info(), usdc_amount)?;
+    let sol_received = amount_received(&ctx.accounts.wrapped_sol_mint.to_account_info(), wrapped_sol_amount)?;
+    let shares = pool_pda.add_liquidity(usdc_received, sol_received)?;
+
+    // the holder of the mint's token owns the position, not whoever opened it
+    position_pda.owner = ctx.accounts.position_mint.key();
+    position_pda.pool = ctx.accounts.pool_pda.key();
+    position_pda.version = USER_VERSION;
+    position_pda.usdc_deposit = usdc_received;
+    position_pda.sol_deposit = sol_received;
+    position_pda.total_shares = shares;
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.user_usdc_ata.to_account_info(),
+        to: ctx.accounts.pool_usdc_ata.to_account_info(),
+        mint: ctx.accounts.usdc_mint.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.usdc_token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, usdc_amount, ctx.accounts.usdc_mint.decimals)?;
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.user_sol_ata.to_account_info(),
+        to: ctx.accounts.pool_sol_ata.to_account_info(),
+        mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.sol_token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, wrapped_sol_amount, ctx.accounts.wrapped_sol_mint.decimals)?;
+
+    // a single token and no mint authority left, so the supply stays at one
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.position_mint.to_account_info(),
+        to: ctx.accounts.position_token_account.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info()
+    };
+    mint_to(CpiContext::new(ctx.accounts.position_token_program.to_account_info(), cpi_accounts), 1)?;
+
+    let cpi_accounts = SetAuthority {
+        current_authority: ctx.accounts.signer.to_account_info(),
+        account_or_mint: ctx.accounts.position_mint.to_account_info()
+    };
+    set_authority(CpiContext::new(ctx.accounts.position_token_program.to_account_info(), cpi_accounts), AuthorityType::MintTokens, None)?;
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(DepositEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        owner: ctx.accounts.signer.key(),
+        usdc_amount: usdc_received,
+        sol_amount: sol_received,
+        shares,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
+
+    msg!("Position {} opened", ctx.accounts.position_mint.key());
+
+    Ok(())
+}
+
+/// withdraws the whole nft position to its holder, burns the token and closes the position
+pub fn process_close_position(ctx: Context<ClosePosition>) -> Result<()> {
+
+    let position_pda = &mut ctx.accounts.position_pda;
+    // the pool signs the transfers below, so its borrow has to end before them
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    let now = Clock::get()?.unix_timestamp;
+    // the position is closed below, so rewards can't be left behind in it
+    update_rewards(&pool_pda, ctx.accounts.reward_vault.as_mut(), position_pda, now)?;
+    require!(position_pda.rewards_owed == 0, PoolError::UnclaimedRewards);
+
+    let shares = position_pda.total_shares;
+    let (usdc_fees, sol_fees) = position_pda.collect_fees(&mut pool_pda)?;
+    let (usdc, sol) = pool_pda.remove_liquidity(shares)?;
+
+    let usdc_out = usdc.checked_add(usdc_fees).ok_or(DepositError::OverFlow)?;
+    let sol_out = sol.checked_add(sol_fees).ok_or(DepositError::OverFlow)?;
+
+    let bump_pool = pool_pda.bump;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    drop(pool_pda);
+
+    let usdc_mint_key = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint_key = ctx.accounts.wrapped_sol_mint.key();
+    let seeds = [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), fee_tier.as_ref(), &[bump_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.pool_usdc_ata.to_account_info(),
+        to: ctx.accounts.user_usdc_ata.to_account_info(),
+        mint: ctx.accounts.usdc_mint.to_account_info(),
+        authority: ctx.accounts.pool_pda.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.usdc_token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, usdc_out, ctx.accounts.usdc_mint.decimals)?;
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.pool_sol_ata.to_account_info(),
+        to: ctx.accounts.user_sol_ata.to_account_info(),
+        mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+        authority: ctx.accounts.pool_pda.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.sol_token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, sol_out, ctx.accounts.wrapped_sol_mint.decimals)?;
+
+    let cpi_accounts = Burn {
+        mint: ctx.accounts.position_mint.to_account_info(),
+        from: ctx.accounts.position_token_account.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info()
+    };
+    burn(CpiContext::new(ctx.accounts.position_token_program.to_account_info(), cpi_accounts), 1)?;
+
+    let cpi_accounts = CloseAccount {
+        account: ctx.accounts.position_token_account.to_account_info(),
+        destination: ctx.accounts.signer.to_account_info(),
+        authority: ctx.accounts.signer.to_account_info()
+    };
+    close_account(CpiContext::new(ctx.accounts.position_token_program.to_account_info(), cpi_accounts))?;
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    let pool_pda = ctx.accounts.pool_pda.load()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(WithdrawEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        owner: ctx.accounts.signer.key(),
+        shares,
+        usdc_amount: usdc,
+        sol_amount: sol,
+        usdc_fees,
+        sol_fees,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
+
+    msg!("Position {} closed", ctx.accounts.position_mint.key());
+
+    Ok(())
+}
+
+/// pays the holder the fees the nft position earned since its last collection
+pub fn process_collect_position_fees(ctx: Context<CollectPositionFees>) -> Result<()> {
+
+    // the pool signs the payouts, so its borrow has to end before them
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+
+    let (usdc_fees, sol_fees) = ctx.accounts.position_pda.collect_fees(&mut pool_pda)?;
+    require!(usdc_fees > 0 || sol_fees > 0, PoolError::NoFeesToCollect);
+
+    let bump_pool = pool_pda.bump;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    drop(pool_pda);
+
+    let usdc_mint_key = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint_key = ctx.accounts.wrapped_sol_mint.key();
+    let seeds = [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), fee_tier.as_ref(), &[bump_pool]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if usdc_fees > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_usdc_ata.to_account_info(),
+            to: ctx.accounts.user_usdc_ata.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.usdc_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, usdc_fees, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if sol_fees > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_sol_ata.to_account_info(),
+            to: ctx.accounts.user_sol_ata.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.sol_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, sol_fees, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    ctx.accounts.pool_pda.load()?.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(FeeCollectEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        owner: ctx.accounts.signer.key(),
+        usdc_amount: usdc_fees,
+        sol_amount: sol_fees
+    });
+
+    Ok(())
+}
+
+pub fn process_claim_position_rewards(ctx: Context<ClaimPositionRewards>) -> Result<()> {
+
+    let now = Clock::get()?.unix_timestamp;
+    let pool_pda = ctx.accounts.pool_pda.load()?;
+    update_rewards(&pool_pda, Some(&mut ctx.accounts.reward_vault), &mut ctx.accounts.position_pda, now)?;
+    drop(pool_pda);
+
+    let position_pda = &mut ctx.accounts.position_pda;
+    let amount = position_pda.rewards_owed;
+    require!(amount > 0, PoolError::NoRewards);
+    position_pda.rewards_owed = 0;
+
+    let pool_key = ctx.accounts.pool_pda.key();
+    let seeds = [b"rewards", pool_key.as_ref(), &[ctx.accounts.reward_vault.bump]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.reward_vault_ata.to_account_info(),
+        to: ctx.accounts.user_reward_ata.to_account_info(),
+        mint: ctx.accounts.reward_mint.to_account_info(),
+        authority: ctx.accounts.reward_vault.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.reward_token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+
+    emit!(RewardClaimEvent {
+        pool: pool_key,
+        owner: ctx.accounts.signer.key(),
+        reward_mint: ctx.accounts.reward_mint.key(),
+        amount
+    });
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{error::{DepositError, PoolError}, events::ReconcileEvent, state::Pool};
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    pub authority: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump,
+        has_one = authority @ PoolError::Unauthorized
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SkimReserves<'info> {
+    pub authority: Signer<'info>,
+    #[account(mint::token_program = usdc_token_program)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = sol_token_program)]
+    pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump,
+        has_one = authority @ PoolError::Unauthorized
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
+    )]
+    pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = wrapped_sol_mint,
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
+    )]
+    pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = usdc_mint,
+        token::token_program = usdc_token_program
+    )]
+    pub recipient_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = wrapped_sol_mint,
+        token::token_program = sol_token_program
+    )]
+    pub recipient_sol_ata: InterfaceAccount<'info, TokenAccount>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+}
+
+/// folds anything sitting in the vaults above reserves + fees into the reserves
+pub fn process_sync(ctx: Context<SyncReserves>) -> Result<()> {
+
+    let usdc_vault = ctx.accounts.pool_usdc_ata.amount;
+    let sol_vault = ctx.accounts.pool_sol_ata.amount;
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    pool_pda.check_vault_balances(usdc_vault, sol_vault)?;
+
+    let usdc_reserve = usdc_vault.checked_sub(pool_pda.fees_collected_usdc).ok_or(DepositError::Underflow)?;
+    let sol_reserve = sol_vault.checked_sub(pool_pda.fees_collected_sol).ok_or(DepositError::Underflow)?;
+    // check_vault_balances leaves these at or above the tracked reserves
+    let (excess_usdc, excess_sol) = (usdc_reserve - pool_pda.total_usdc_deposit, sol_reserve - pool_pda.total_sol_deposit);
+    pool_pda.total_usdc_deposit = usdc_reserve;
+    pool_pda.total_sol_deposit = sol_reserve;
+
+    emit!(ReconcileEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        authority: ctx.accounts.authority.key(),
+        skim: false,
+        usdc_amount: excess_usdc,
+        sol_amount: excess_sol,
+        usdc_reserve,
+        sol_reserve
+    });
+
+    msg!("Pool synced to {} usdc and {} sol", usdc_reserve, sol_reserve);
+
+    Ok(())
+}
+
+/// sends anything sitting in the vaults above reserves + fees to the recipient
+pub fn process_skim(ctx: Context<SkimReserves>) -> Result<()> {
+
+    // copied out so the pool isn't borrowed while it signs the transfers
+    let pool_pda = *ctx.accounts.pool_pda.load()?;
+
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    let excess_usdc = ctx.accounts.pool_usdc_ata.amount - (pool_pda.total_usdc_deposit + pool_pda.fees_collected_usdc);
+    let excess_sol = ctx.accounts.pool_sol_ata.amount - (pool_pda.total_sol_deposit + pool_pda.fees_collected_sol);
+
+    let usdc_mint = ctx.accounts.usdc_mint.key();
+    let wrapped_sol_mint = ctx.accounts.wrapped_sol_mint.key();
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
+    let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), fee_tier.as_ref(), &[pool_pda.bump]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if excess_usdc > 0 {
+        let usdc_cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_usdc_ata.to_account_info(),
+            to: ctx.accounts.recipient_usdc_ata.to_account_info(),
+            mint: ctx.accounts.usdc_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+
+        let usdc_ctx = CpiContext::new_with_signer(
+            ctx.accounts.usdc_token_program.to_account_info(),
+            usdc_cpi_accounts,
+            signer_seeds
+        );
+        transfer_checked(usdc_ctx, excess_usdc, ctx.accounts.usdc_mint.decimals)?;
+    }
+
+    if excess_sol > 0 {
+        let sol_cpi_accounts = TransferChecked {
+            from: ctx.accounts.pool_sol_ata.to_account_info(),
+            to: ctx.accounts.recipient_sol_ata.to_account_info(),
+            mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
+            authority: ctx.accounts.pool_pda.to_account_info()
+        };
+
+        let sol_ctx = CpiContext::new_with_signer(
+            ctx.accounts.sol_token_program.to_account_info(),
+            sol_cpi_accounts,
+            signer_seeds
+        );
+        transfer_checked(sol_ctx, excess_sol, ctx.accounts.wrapped_sol_mint.decimals)?;
+    }
+
+    emit!(ReconcileEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        authority: ctx.accounts.authority.key(),
+        skim: true,
+        usdc_amount: excess_usdc,
+        sol_amount: excess_sol,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
+
+    msg!("Skimmed {} usdc and {} sol", excess_usdc, excess_sol);
+
+    Ok(())
+}
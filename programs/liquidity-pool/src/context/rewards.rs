@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}};
+
+use crate::{error::PoolError, events::RewardClaimEvent, extensions::{amount_to_send, validate_mint}, state::{Pool, RewardVault, User}};
+
+#[derive(Accounts)]
+pub struct InitializeRewards<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ PoolError::Unauthorized
+    )]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(mint::token_program = reward_token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RewardVault::INIT_SPACE,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = reward_vault,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = reward_token_program
+    )]
+    pub authority_reward_ata: InterfaceAccount<'info, TokenAccount>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"lp", pool_pda.key().as_ref(), signer.key().as_ref()],
+        bump
+    )]
+    pub user_pda: Account<'info, User>,
+    #[account(mut)]
+    pub pool_pda: AccountLoader<'info, Pool>,
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump,
+        has_one = reward_mint
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+    #[account(mint::token_program = reward_token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = reward_vault,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = reward_token_program
+    )]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>
+}
+
+/// brings the pool's reward accumulator and the position's checkpoint up to date. runs
+/// before anything changes the shares or boost, pools with rewards have to pass their vault.
+pub fn update_rewards(pool: &Pool, reward_vault: Option<&mut Account<RewardVault>>, user: &mut User, now: i64) -> Result<()> {
+    let Some(reward_vault) = reward_vault else {
+        require!(pool.rewards_enabled == 0, PoolError::MissingRewardVault);
+        return Ok(());
+    };
+
+    reward_vault.accrue(pool.reward_weight()?, now)?;
+    user.settle_rewards(reward_vault)
+}
+
+/// creates the pool's reward vault and funds it with the whole schedule up front, so
+/// claims can never run dry
+pub fn process_initialize_rewards(ctx: Context<InitializeRewards>, emission_rate: u64, start_time: i64, end_time: i64) -> Result<()> {
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(emission_rate > 0 && now <= start_time && start_time < end_time, PoolError::InvalidRewardSchedule);
+    validate_mint(&ctx.accounts.reward_mint.to_account_info())?;
+
+    ctx.accounts.pool_pda.load_mut()?.rewards_enabled = 1;
+
+    let reward_vault = &mut ctx.accounts.reward_vault;
+    reward_vault.pool = ctx.accounts.pool_pda.key();
+    reward_vault.reward_mint = ctx.accounts.reward_mint.key();
+    reward_vault.emission_rate = emission_rate;
+    reward_vault.start_time = start_time;
+    reward_vault.end_time = end_time;
+    reward_vault.last_update_time = start_time;
+    reward_vault.reward_per_share = 0;
+    reward_vault.bump = ctx.bumps.reward_vault;
+
+    let total_emissions = reward_vault.total_emissions()?;
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.authority_reward_ata.to_account_info(),
+        to: ctx.accounts.reward_vault_ata.to_account_info(),
+        mint: ctx.accounts.reward_mint.to_account_info(),
+        authority: ctx.accounts.authority.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.reward_token_program.to_account_info(), cpi_accounts);
+    // token-2022 transfer fees are paid on top so the vault holds the full schedule
+    let amount = amount_to_send(&ctx.accounts.reward_mint.to_account_info(), total_emissions)?;
+    transfer_checked(cpi_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+
+    msg!("Rewards of {} per second from {} to {}", emission_rate, start_time, end_time);
+
+    Ok(())
+}
+
+pub fn process_claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    update_rewards(&pool_pda, Some(&mut ctx.accounts.reward_vault), &mut ctx.accounts.user_pda, now)?;
+    ctx.accounts.user_pda.refresh_boost(&mut pool_pda, now)?;
+    drop(pool_pda);
+
+    let user_pda = &mut ctx.accounts.user_pda;
+    let amount = user_pda.rewards_owed;
+    require!(amount > 0, PoolError::NoRewards);
+    user_pda.rewards_owed = 0;
+
+    let pool_key = ctx.accounts.pool_pda.key();
+    let seeds = [b"rewards", pool_key.as_ref(), &[ctx.accounts.reward_vault.bump]];
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.reward_vault_ata.to_account_info(),
+        to: ctx.accounts.user_reward_ata.to_account_info(),
+        mint: ctx.accounts.reward_mint.to_account_info(),
+        authority: ctx.accounts.reward_vault.to_account_info()
+    };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.reward_token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+
+    emit!(RewardClaimEvent {
+        pool: pool_key,
+        owner: ctx.accounts.signer.key(),
+        reward_mint: ctx.accounts.reward_mint.key(),
+        amount
+    });
+
+    Ok(())
+}
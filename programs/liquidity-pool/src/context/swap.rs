@@ -1,18 +1,21 @@
 
 use anchor_lang::{prelude::*};
 
-use anchor_spl::{associated_token::AssociatedToken, token::{transfer_checked, TransferChecked}, token_interface::{Mint, TokenAccount, TokenInterface}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}};
 
 use crate::{ state::Pool};
+use crate::events::SwapEvent;
 use crate::error::{PoolError,DepositError};
+use crate::extensions::{amount_received, amount_to_send};
+use crate::native_sol::{require_native_mint, unwrap_sol, wrap_sol};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
-    #[account(mut)]
+    #[account(mut, mint::token_program = usdc_token_program)]
     pub usdc_mint: InterfaceAccount<'info, Mint>,
-    #[account(mut)]
+    #[account(mut, mint::token_program = sol_token_program)]
     pub wrapped_sol_mint:InterfaceAccount<'info, Mint>,
     #[account(mut)]
     pub user_quote_ata: InterfaceAccount<'info, TokenAccount>,
@@ -21,62 +24,75 @@ pub struct Swap<'info> {
         payer = signer , 
         associated_token::mint = base_mint, 
         associated_token::authority = signer,  
-        associated_token::token_program = token_program
+        associated_token::token_program = base_token_program
     )]
     pub user_base_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut, 
-        constraint = (base_mint.key() != user_quote_ata.mint.key())
+        constraint = (base_mint.key() != user_quote_ata.mint.key()),
+        mint::token_program = base_token_program
     )]
     pub base_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
-        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref()],
-        bump = pool_pda.bump
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
+        bump = pool_pda.load()?.bump
     )]
-    pub pool_pda: Account<'info, Pool>,
+    pub pool_pda: AccountLoader<'info, Pool>,
     #[account(
         mut, 
         associated_token::mint = usdc_mint, 
-        associated_token::authority = pool_pda, 
+        associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
     )]
     pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut, 
         associated_token::mint = wrapped_sol_mint, 
-        associated_token::authority = pool_pda, 
+        associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
     )]
     pub pool_sol_ata: InterfaceAccount<'info, TokenAccount>,
-    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
+    pub base_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>
 }
 
 
-pub fn process_swap(ctx: Context<Swap>, swap_amount: u64) -> Result<()> {
+pub fn process_swap(ctx: Context<Swap>, swap_amount: u64, min_amount_out: u64, native_sol: bool) -> Result<()> {
 
     let user_base_asset_key = ctx.accounts.user_base_ata.mint.key();
     let user_quote_asset_key = ctx.accounts.user_quote_ata.mint.key();
     let usdc_mint = ctx.accounts.usdc_mint.key();
     let wrapped_sol_mint = ctx.accounts.wrapped_sol_mint.key();
-    let bump_pool = ctx.accounts.pool_pda.bump;
-    let pool_pda =&mut  ctx.accounts.pool_pda;
+    // the pool signs the payout, so each branch drops this borrow before transferring
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    let bump_pool = pool_pda.bump;
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
 
     require!(swap_amount > 0, DepositError::ZeroAmountError);
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
 
     require!((user_base_asset_key != user_quote_asset_key), DepositError::InvalidAccountInputs);
     require!((user_base_asset_key == usdc_mint || user_base_asset_key == wrapped_sol_mint), DepositError::InvalidAccounts);
     require!((user_quote_asset_key == usdc_mint || user_quote_asset_key == wrapped_sol_mint), DepositError::InvalidAccounts);
 
-    if user_quote_asset_key == usdc_mint {
-        let total_usdc = pool_pda.total_usdc_deposit.checked_add(swap_amount).ok_or(DepositError::Underflow)? ;
-        let total_sol = pool_pda.total_sol_deposit;
-        let constant_product = total_sol as u128 * pool_pda.total_usdc_deposit as u128;
-        let remaining_sol = constant_product.checked_div(total_usdc as u128).ok_or(DepositError::DivisionError)? as u64;
-        let sol_to_be_transfered = total_sol.checked_sub(remaining_sol).ok_or(DepositError::Underflow)?;
+    if native_sol {
+        require_native_mint(&wrapped_sol_mint)?;
+    }
 
-        let transaction_fee  = swap_amount.checked_mul(pool_pda.liquidity_fees).unwrap().checked_div(10_000).unwrap();
+    let fee_bps = pool_pda.swap_fee_bps(Clock::get()?.unix_timestamp)?;
+
+    let (usdc_in, amount_in, amount_out, fee) = if user_quote_asset_key == usdc_mint {
+        let (sol_to_be_transfered, transaction_fee) = pool_pda.swap_usdc_for_sol(swap_amount, fee_bps)?;
+        require!(sol_to_be_transfered >= min_amount_out, PoolError::SlippageExceeded);
         let required_usdc = swap_amount.checked_add(transaction_fee).ok_or(DepositError::OverFlow)?;
+        // gross up so the vault receives exactly what the curve expects
+        let required_usdc = amount_to_send(&ctx.accounts.usdc_mint.to_account_info(), required_usdc)?;
+        pool_pda.update_volatility()?;
+        drop(pool_pda);
 
         let usdc_cpi_accounts = TransferChecked {
             from: ctx.accounts.user_quote_ata.to_account_info(),
@@ -86,51 +102,56 @@ pub fn process_swap(ctx: Context<Swap>, swap_amount: u64) -> Result<()> {
         };
 
         let usdc_cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(), 
+            ctx.accounts.usdc_token_program.to_account_info(), 
             usdc_cpi_accounts);
         
         transfer_checked(usdc_cpi_ctx, required_usdc, ctx.accounts.usdc_mint.decimals)?;
 
-        let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(),&[bump_pool]];
+        let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), fee_tier.as_ref(), &[bump_pool]];
 
         let sol_cpi_account = TransferChecked {
             from:ctx.accounts.pool_sol_ata.to_account_info(), 
             to: ctx.accounts.user_base_ata.to_account_info(), 
             mint: ctx.accounts.wrapped_sol_mint.to_account_info(),
-            authority: pool_pda.to_account_info()
+            authority: ctx.accounts.pool_pda.to_account_info()
         };
 
         let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
 
         let sol_cpi = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(), 
+            ctx.accounts.sol_token_program.to_account_info(), 
             sol_cpi_account,
             signer_seeds
         );
         
         transfer_checked(sol_cpi, sol_to_be_transfered, ctx.accounts.wrapped_sol_mint.decimals)?;
 
-        //update pool 
-        pool_pda.total_sol_deposit = remaining_sol;
-        pool_pda.total_usdc_deposit += swap_amount;
-        pool_pda.fees_collected_usdc += transaction_fee
+        if native_sol {
+            unwrap_sol(
+                ctx.accounts.signer.to_account_info(),
+                ctx.accounts.user_base_ata.to_account_info(),
+                ctx.accounts.sol_token_program.to_account_info()
+            )?;
+        }
 
+        (true, swap_amount + transaction_fee, sol_to_be_transfered, transaction_fee)
     }else if user_quote_asset_key == wrapped_sol_mint {
 
-        let total_sol = pool_pda.total_sol_deposit;
-        let total_usdc =pool_pda.total_usdc_deposit;
-
-        let total_updated_sol = total_sol.checked_add(swap_amount).ok_or(DepositError::OverFlow)?;
-        let constant_product = total_sol as u128 * total_usdc as u128;
-        let required_usdc = pool_pda.total_usdc_deposit - constant_product.checked_div(total_updated_sol as u128).ok_or(DepositError::DivisionError)? as u64;
-        let fee_required = ((required_usdc as f64 )* ((pool_pda.liquidity_fees as f64 / 100_00.0)) )as u64;
-        let usdc_to_be_paid = required_usdc.checked_sub(fee_required).ok_or(DepositError::Underflow)? ;
-
-        require!( pool_pda.total_usdc_deposit >= usdc_to_be_paid + fee_required, PoolError::InsufficientLiquidity);
-
-        pool_pda.total_sol_deposit += swap_amount;
-        pool_pda.total_usdc_deposit -= usdc_to_be_paid + fee_required;
-        pool_pda.fees_collected_usdc += fee_required;
+        let sol_received = amount_received(&ctx.accounts.wrapped_sol_mint.to_account_info(), swap_amount)?;
+        let (usdc_to_be_paid, fee_required) = pool_pda.swap_sol_for_usdc(sol_received, fee_bps)?;
+        require!(usdc_to_be_paid >= min_amount_out, PoolError::SlippageExceeded);
+        pool_pda.update_volatility()?;
+        drop(pool_pda);
+
+        if native_sol {
+            wrap_sol(
+                ctx.accounts.signer.to_account_info(),
+                ctx.accounts.user_quote_ata.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.sol_token_program.to_account_info(),
+                swap_amount
+            )?;
+        }
 
         let sol_cpi_accounts = TransferChecked {
             from: ctx.accounts.user_quote_ata.to_account_info(), 
@@ -140,7 +161,7 @@ pub fn process_swap(ctx: Context<Swap>, swap_amount: u64) -> Result<()> {
         };
 
         let sol_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(), 
+            ctx.accounts.sol_token_program.to_account_info(), 
             sol_cpi_accounts);
         
         transfer_checked(sol_ctx, swap_amount, ctx.accounts.wrapped_sol_mint.decimals)?;
@@ -153,17 +174,46 @@ pub fn process_swap(ctx: Context<Swap>, swap_amount: u64) -> Result<()> {
             authority: ctx.accounts.pool_pda.to_account_info()   
         };
 
-        let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), &[bump_pool]];
+        let seeds = [b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref(), fee_tier.as_ref(), &[bump_pool]];
 
         let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
 
         let usdc_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(), 
+            ctx.accounts.usdc_token_program.to_account_info(), 
             usdc_cpi_accounts, 
             signer_seeds
         );
         transfer_checked(usdc_ctx, usdc_to_be_paid, ctx.accounts.usdc_mint.decimals)?;
-    }
+
+        if native_sol {
+            unwrap_sol(
+                ctx.accounts.signer.to_account_info(),
+                ctx.accounts.user_quote_ata.to_account_info(),
+                ctx.accounts.sol_token_program.to_account_info()
+            )?;
+        }
+
+        (false, sol_received, usdc_to_be_paid, fee_required)
+    } else {
+        return err!(DepositError::InvalidAccounts);
+    };
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_sol_ata.reload()?;
+    let pool_pda = ctx.accounts.pool_pda.load()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_sol_ata.amount)?;
+
+    emit!(SwapEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        trader: ctx.accounts.signer.key(),
+        usdc_in,
+        amount_in,
+        amount_out,
+        usdc_fee: fee,
+        sol_fee: 0,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
 
     Ok(())
 }
\ No newline at end of file
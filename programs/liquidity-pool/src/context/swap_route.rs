@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::get_associated_token_address_with_program_id, token::Token, token_2022::Token2022, token_interface::{transfer_checked, Mint, TokenAccount, TransferChecked}};
+
+use crate::{error::{DepositError, PoolError}, events::SwapEvent, extensions::amount_received, state::{Pool, ACCOUNTS_PER_HOP, MAX_ROUTE_HOPS}};
+
+/// remaining accounts carry `ACCOUNTS_PER_HOP` accounts per hop:
+/// pool_pda, usdc_mint, wrapped_sol_mint, pool_usdc_ata, pool_sol_ata
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        token::authority = signer
+    )]
+    pub user_source_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub user_destination_ata: InterfaceAccount<'info, TokenAccount>,
+    // hops can mix classic and token-2022 mints, each transfer picks the mint's program
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+impl<'info> SwapRoute<'info> {
+    fn program_for(&self, mint: &AccountInfo<'info>) -> AccountInfo<'info> {
+        if *mint.owner == self.token_2022_program.key() {
+            self.token_2022_program.to_account_info()
+        } else {
+            self.token_program.to_account_info()
+        }
+    }
+}
+
+pub fn process_swap_route<'info>(ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>, amount_in: u64, min_amount_out: u64, hops: u8) -> Result<()> {
+
+    require!(amount_in > 0, DepositError::ZeroAmountError);
+    require!(hops > 0 && hops as usize <= MAX_ROUTE_HOPS, PoolError::InvalidRoute);
+    require!(ctx.remaining_accounts.len() == hops as usize * ACCOUNTS_PER_HOP, PoolError::InvalidRoute);
+
+    let hop_accounts: Vec<&[AccountInfo<'info>]> = ctx.remaining_accounts.chunks(ACCOUNTS_PER_HOP).collect();
+    let now = Clock::get()?.unix_timestamp;
+
+    // the same pool twice would have its state overwritten by the later hop
+    for (i, hop) in hop_accounts.iter().enumerate() {
+        require!(hop_accounts[..i].iter().all(|other| other[0].key() != hop[0].key()), PoolError::InvalidRoute);
+    }
+
+    let mut amount = amount_in;
+    let mut input_mint = ctx.accounts.user_source_ata.mint;
+
+    for (i, hop) in hop_accounts.iter().enumerate() {
+        let pool_loader = AccountLoader::<Pool>::try_from(&hop[0])?;
+        let mut pool_pda = pool_loader.load_mut()?;
+        let usdc_mint = InterfaceAccount::<Mint>::try_from(&hop[1])?;
+        let wrapped_sol_mint = InterfaceAccount::<Mint>::try_from(&hop[2])?;
+        let pool_usdc_ata = &hop[3];
+        let pool_sol_ata = &hop[4];
+
+        let usdc_mint_key = usdc_mint.key();
+        let wrapped_sol_mint_key = wrapped_sol_mint.key();
+        let fee_tier = pool_pda.fee_tier.to_le_bytes();
+        let seeds = [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), fee_tier.as_ref(), &[pool_pda.bump]];
+
+        let pool_key = Pubkey::create_program_address(&seeds, &crate::ID).map_err(|_| PoolError::InvalidRoute)?;
+        require_keys_eq!(pool_key, pool_loader.key(), PoolError::InvalidRoute);
+        require_keys_eq!(pool_usdc_ata.key(), get_associated_token_address_with_program_id(&pool_key, &usdc_mint_key, hop[1].owner), PoolError::InvalidRoute);
+        require_keys_eq!(pool_sol_ata.key(), get_associated_token_address_with_program_id(&pool_key, &wrapped_sol_mint_key, hop[2].owner), PoolError::InvalidRoute);
+        require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+
+        let usdc_in = if input_mint == usdc_mint_key {
+            true
+        } else {
+            require_keys_eq!(input_mint, wrapped_sol_mint_key, PoolError::InvalidRoute);
+            false
+        };
+
+        let (vault_in, vault_out, mint_in, mint_out) = if usdc_in {
+            (pool_usdc_ata, pool_sol_ata, &usdc_mint, &wrapped_sol_mint)
+        } else {
+            (pool_sol_ata, pool_usdc_ata, &wrapped_sol_mint, &usdc_mint)
+        };
+
+        if i == 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.user_source_ata.to_account_info(),
+                to: vault_in.clone(),
+                mint: mint_in.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info()
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.program_for(&mint_in.to_account_info()), cpi_accounts);
+            transfer_checked(cpi_ctx, amount, mint_in.decimals)?;
+            amount = amount_received(&mint_in.to_account_info(), amount)?;
+        }
+
+        let fee_bps = pool_pda.swap_fee_bps(now)?;
+
+        let (amount_out, fee) = if usdc_in {
+            // the fee is paid on top of the curve amount, so split what arrived
+            let curve_amount = ((amount as u128) * 10_000 / (10_000 + fee_bps as u128)) as u64;
+            let (sol_out, fee) = pool_pda.swap_usdc_for_sol(curve_amount, fee_bps)?;
+            let dust = amount.checked_sub(curve_amount + fee).ok_or(DepositError::Underflow)?;
+            pool_pda.add_fees(dust, 0)?;
+            (sol_out, fee + dust)
+        } else {
+            pool_pda.swap_sol_for_usdc(amount, fee_bps)?
+        };
+        pool_pda.update_volatility()?;
+        // the pool signs the payout, its borrow has to end first
+        drop(pool_pda);
+
+        require!(amount_out > 0, PoolError::InsufficientLiquidity);
+
+        let output_mint = mint_out.key();
+
+        // intermediate output goes straight into the next pool's vault
+        let destination = match hop_accounts.get(i + 1) {
+            Some(next) => {
+                if next[1].key() == output_mint { next[3].clone() } else { next[4].clone() }
+            }
+            None => ctx.accounts.user_destination_ata.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+        let cpi_accounts = TransferChecked {
+            from: vault_out.clone(),
+            to: destination,
+            mint: mint_out.to_account_info(),
+            authority: pool_loader.to_account_info()
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.program_for(&mint_out.to_account_info()), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount_out, mint_out.decimals)?;
+
+        let usdc_vault = InterfaceAccount::<TokenAccount>::try_from(pool_usdc_ata)?;
+        let sol_vault = InterfaceAccount::<TokenAccount>::try_from(pool_sol_ata)?;
+        let pool_pda = pool_loader.load()?;
+        pool_pda.check_vault_balances(usdc_vault.amount, sol_vault.amount)?;
+
+        emit!(SwapEvent {
+            pool: pool_loader.key(),
+            trader: ctx.accounts.signer.key(),
+            usdc_in,
+            amount_in: amount,
+            amount_out,
+            usdc_fee: fee,
+            sol_fee: 0,
+            usdc_reserve: pool_pda.total_usdc_deposit,
+            sol_reserve: pool_pda.total_sol_deposit
+        });
+        drop(pool_pda);
+
+        // the next hop (or the user) only gets what survives the transfer fee
+        amount = amount_received(&mint_out.to_account_info(), amount_out)?;
+        input_mint = output_mint;
+    }
+
+    require_keys_eq!(ctx.accounts.user_destination_ata.mint, input_mint, PoolError::InvalidRoute);
+    require!(amount >= min_amount_out, PoolError::SlippageExceeded);
+
+    msg!("Routed {} through {} pools for {}", amount_in, hops, amount);
+
+    Ok(())
+}
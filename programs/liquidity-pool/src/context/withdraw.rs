@@ -1,21 +1,21 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token::{transfer_checked, TransferChecked}, token_interface::{Mint, TokenAccount, TokenInterface}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked}};
 
-use crate::{error::DepositError, state::{Pool, User}};
+use crate::{context::update_rewards, error::{DepositError, PoolError}, events::WithdrawEvent, native_sol::{require_native_mint, unwrap_sol}, state::{Pool, RewardVault, User}};
 
 
 #[derive(Accounts)]
 pub struct WithDraw<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
-    #[account(mut)]
+    #[account(mut, mint::token_program = usdc_token_program)]
     pub usdc_mint: InterfaceAccount<'info, Mint>,
-    #[account(mut)]
+    #[account(mut, mint::token_program = sol_token_program)]
     pub wrapped_sol_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         close = signer, 
-        seeds = [b"lp", signer.key().as_ref()],
+        seeds = [b"lp", pool_pda.key().as_ref(), signer.key().as_ref()],
         bump
     )]
     pub user_pda: Account<'info, User>,
@@ -24,72 +24,91 @@ pub struct WithDraw<'info> {
         payer = signer,
         associated_token::mint = usdc_mint,
         associated_token::authority = signer, 
-        associated_token::token_program = token_program
+        associated_token::token_program = usdc_token_program
     )]
     pub user_usdc_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         init_if_needed, 
         payer = signer, 
         associated_token::mint = wrapped_sol_mint,
-        associated_token::token_program = token_program,
+        associated_token::token_program = sol_token_program,
         associated_token::authority = signer,
     )]
     pub user_sol_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut, 
-        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref()],
+        seeds = [b"pool", usdc_mint.key().as_ref(), wrapped_sol_mint.key().as_ref(), pool_pda.load()?.fee_tier.to_le_bytes().as_ref()],
         bump
     )]
-    pub pool_pda: Account<'info, Pool>,
+    pub pool_pda: AccountLoader<'info, Pool>,
     #[account(
         mut,
         associated_token::mint = usdc_mint, 
         associated_token::authority = pool_pda,
+        associated_token::token_program = usdc_token_program
     )]
     pub pool_usdc_ata: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = wrapped_sol_mint, 
         associated_token::authority = pool_pda,
+        associated_token::token_program = sol_token_program
     )]
     pub pool_wrapped_sol_ata: InterfaceAccount<'info, TokenAccount>,
-    pub token_program: Interface<'info, TokenInterface>,
+    pub usdc_token_program: Interface<'info, TokenInterface>,
+    pub sol_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>
+    pub system_program: Program<'info, System>,
+    /// required once the pool has rewards
+    #[account(
+        mut,
+        seeds = [b"rewards", pool_pda.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>
 }
 
-pub fn process_withdraw(ctx: Context<WithDraw>) -> Result<()> {
+pub fn process_withdraw(ctx: Context<WithDraw>, native_sol: bool) -> Result<()> {
 
     let user_pda = &mut ctx.accounts.user_pda; 
-    let pool_pda =&mut ctx.accounts.pool_pda;
-    let total_fee = pool_pda.fees_collected_usdc;
-    let total_sol= pool_pda.total_sol_deposit;
-    let total_usdc = pool_pda.total_usdc_deposit;
+    // the pool signs the transfers below, so its borrow has to end before them
+    let mut pool_pda = ctx.accounts.pool_pda.load_mut()?;
+    require!(pool_pda.flash_loan_active == 0, PoolError::FlashLoanActive);
+    let now = Clock::get()?.unix_timestamp;
+    require!(!user_pda.is_locked(now), PoolError::PositionLocked);
+    // the position is closed below, so rewards can't be left behind in it
+    update_rewards(&pool_pda, ctx.accounts.reward_vault.as_mut(), user_pda, now)?;
+    require!(user_pda.rewards_owed == 0, PoolError::UnclaimedRewards);
+    // drops the expired lock's boost from the pool
+    user_pda.refresh_boost(&mut pool_pda, now)?;
+
     let user_shares = user_pda.total_shares;
-    let total_shares = pool_pda.total_shares; 
     let wrapped_sol_mint_key = ctx.accounts.wrapped_sol_mint.to_account_info().key();
     let usdc_mint_key = ctx.accounts.usdc_mint.to_account_info().key();
+    let fee_tier = pool_pda.fee_tier.to_le_bytes();
 
-    let user_owned_portion = (user_shares as f64) / (total_shares as f64);
-
-    let user_sol = ((total_sol as f64) * user_owned_portion) as u64;
-    let user_usdc = ((total_usdc as f64) * user_owned_portion) as u64;
-    let user_reward =( (total_fee as f64) * user_owned_portion) as u64;
+    //update pool
+    let (user_reward, user_sol_reward) = user_pda.collect_fees(&mut pool_pda)?;
+    let (user_usdc, user_sol) = pool_pda.remove_liquidity(user_shares)?;
 
     let tota_usdc_to_be_paid = user_usdc.checked_add(user_reward).ok_or(DepositError::OverFlow)?;
+    let total_sol_to_be_paid = user_sol.checked_add(user_sol_reward).ok_or(DepositError::OverFlow)?;
+
+    let bump_pool = pool_pda.bump;
+    drop(pool_pda);
 
     let usdc_cpi_accounts = TransferChecked {
         from: ctx.accounts.pool_usdc_ata.to_account_info(), 
         to: ctx.accounts.user_usdc_ata.to_account_info(), 
         mint: ctx.accounts.usdc_mint.to_account_info(), 
-        authority: pool_pda.to_account_info()
+        authority: ctx.accounts.pool_pda.to_account_info()
     };
 
-    let seeds= [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), &[pool_pda.bump]];
+    let seeds= [b"pool", usdc_mint_key.as_ref(), wrapped_sol_mint_key.as_ref(), fee_tier.as_ref(), &[bump_pool]];
     let signer_seeds: &[&[&[u8]]] =&[&seeds[..]];
 
     let usdc_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.usdc_token_program.to_account_info(),
         usdc_cpi_accounts,
         signer_seeds
     );
@@ -100,23 +119,42 @@ pub fn process_withdraw(ctx: Context<WithDraw>) -> Result<()> {
         from: ctx.accounts.pool_wrapped_sol_ata.to_account_info(),
         to: ctx.accounts.user_sol_ata.to_account_info(), 
         mint: ctx.accounts.wrapped_sol_mint.to_account_info(), 
-        authority: pool_pda.to_account_info() 
+        authority: ctx.accounts.pool_pda.to_account_info() 
     };
 
     let sol_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.sol_token_program.to_account_info(),
         sol_cpi_accounts,
         signer_seeds
     );
 
-    transfer_checked(sol_ctx, user_sol, ctx.accounts.wrapped_sol_mint.decimals)?;
-
-    //update pool
-
-    pool_pda.total_shares -= user_shares;
-    pool_pda.total_sol_deposit -= user_sol; 
-    pool_pda.total_usdc_deposit -= user_usdc;
-    pool_pda.fees_collected_usdc -= user_reward;
+    transfer_checked(sol_ctx, total_sol_to_be_paid, ctx.accounts.wrapped_sol_mint.decimals)?;
+
+    if native_sol {
+        require_native_mint(&wrapped_sol_mint_key)?;
+        unwrap_sol(
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.user_sol_ata.to_account_info(),
+            ctx.accounts.sol_token_program.to_account_info()
+        )?;
+    }
+
+    ctx.accounts.pool_usdc_ata.reload()?;
+    ctx.accounts.pool_wrapped_sol_ata.reload()?;
+    let pool_pda = ctx.accounts.pool_pda.load()?;
+    pool_pda.check_vault_balances(ctx.accounts.pool_usdc_ata.amount, ctx.accounts.pool_wrapped_sol_ata.amount)?;
+
+    emit!(WithdrawEvent {
+        pool: ctx.accounts.pool_pda.key(),
+        owner: ctx.accounts.signer.key(),
+        shares: user_shares,
+        usdc_amount: user_usdc,
+        sol_amount: user_sol,
+        usdc_fees: user_reward,
+        sol_fees: user_sol_reward,
+        usdc_reserve: pool_pda.total_usdc_deposit,
+        sol_reserve: pool_pda.total_sol_deposit
+    });
 
     msg!("Withdraw successfull for {}", ctx.accounts.signer.key().to_string());
 
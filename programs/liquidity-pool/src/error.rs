@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use liquidity_pool_math::MathError;
 
 #[error_code]
 pub enum DepositError {
@@ -28,6 +29,74 @@ pub enum PoolError {
     #[msg("pool error zero shares")]
     ZeroShares,
     #[msg("Insufficient liquidity")]
-    InsufficientLiquidity
+    InsufficientLiquidity,
+    #[msg("invalid fee bounds")]
+    InvalidFeeBounds,
+    #[msg("signer is not the pool authority")]
+    Unauthorized,
+    #[msg("fee tier is not whitelisted")]
+    InvalidFeeTier,
+    #[msg("fee tier already whitelisted")]
+    DuplicateFeeTier,
+    #[msg("too many fee tiers")]
+    FeeTierLimit,
+    #[msg("flash loan in progress")]
+    FlashLoanActive,
+    #[msg("no flash loan to repay")]
+    FlashLoanNotActive,
+    #[msg("flash repay instruction missing")]
+    MissingFlashRepay,
+    #[msg("instruction can not be called through cpi")]
+    CpiNotAllowed,
+    #[msg("pool invariant violated")]
+    InvariantViolated,
+    #[msg("invalid swap route")]
+    InvalidRoute,
+    #[msg("output below minimum amount")]
+    SlippageExceeded,
+    #[msg("mint has an unsupported token-2022 extension")]
+    UnsupportedMintExtension,
+    #[msg("native sol mode needs the native mint")]
+    NotNativeMint,
+    #[msg("account is already on the latest version")]
+    AlreadyMigrated,
+    #[msg("pool has rewards, its reward vault is required")]
+    MissingRewardVault,
+    #[msg("invalid reward schedule")]
+    InvalidRewardSchedule,
+    #[msg("no rewards to claim")]
+    NoRewards,
+    #[msg("claim rewards before withdrawing")]
+    UnclaimedRewards,
+    #[msg("lock duration out of range")]
+    InvalidLockDuration,
+    #[msg("lock can only be extended")]
+    LockShortened,
+    #[msg("position is locked")]
+    PositionLocked,
+    #[msg("no fees to compound")]
+    NoFeesToCompound,
+    #[msg("no fees to collect")]
+    NoFeesToCollect,
+    #[msg("signer does not hold the position token")]
+    NotPositionHolder,
+    #[msg("pool owes no baseline shares for this position")]
+    LegacySharesExceeded,
+    #[msg("signer is not the program's upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("position has no expired lock")]
+    LockNotExpired
 }
 
+
+/// maps the shared math crate's errors onto the codes the program has always returned
+pub fn math_error(err: MathError) -> Error {
+    match err {
+        MathError::Overflow => DepositError::OverFlow.into(),
+        MathError::Underflow => DepositError::Underflow.into(),
+        MathError::DivisionByZero => DepositError::DivisionError.into(),
+        MathError::ImbalancedDeposit => PoolError::ImbalancedDeposit.into(),
+        MathError::ZeroShares => PoolError::ZeroShares.into(),
+        MathError::InsufficientLiquidity => PoolError::InsufficientLiquidity.into(),
+    }
+}
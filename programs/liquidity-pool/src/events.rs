@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+// reserves are the pool's tracked reserves after the instruction, fee buckets excluded
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepositEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    /// what the vaults received, after token-2022 transfer fees
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    pub shares: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+/// emitted by swap, flash_swap and once per hop by swap_route
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub usdc_in: bool,
+    /// what the pool received, for usdc swaps this includes the fee
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// swap and swap_route only charge usdc, a flash swap pays a fee on each side it repays
+    pub usdc_fee: u64,
+    pub sol_fee: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    pub usdc_fees: u64,
+    pub sol_fees: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+/// fees moved into the reserves, the keeper's cut excluded
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompoundEvent {
+    pub pool: Pubkey,
+    pub keeper: Pubkey,
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    pub keeper_usdc: u64,
+    pub keeper_sol: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewardClaimEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+}
+
+/// fees paid to a position that keeps its shares
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeCollectEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+}
+
+/// tokens sent straight to the vaults, folded into the reserves by sync or sent out by skim
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconcileEvent {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub skim: bool,
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+/// emitted by flash_repay, the fees go to the lp buckets
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlashLoanEvent {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub usdc_amount: u64,
+    pub sol_amount: u64,
+    pub usdc_fee: u64,
+    pub sol_fee: u64,
+    pub usdc_reserve: u64,
+    pub sol_reserve: u64,
+}
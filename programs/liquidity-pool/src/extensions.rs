@@ -0,0 +1,176 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+};
+
+use crate::error::PoolError;
+
+/// mint extensions the pool can hold safely, anything else is rejected at pool creation
+const SUPPORTED_EXTENSIONS: [ExtensionType; 7] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::GroupPointer,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+];
+
+pub fn validate_mint(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+
+    for extension in state.get_extension_types()? {
+        require!(SUPPORTED_EXTENSIONS.contains(&extension), PoolError::UnsupportedMintExtension);
+    }
+
+    Ok(())
+}
+
+/// transfer fee withheld when `amount` is sent, 0 for mints without the extension
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => {
+            let fee = config
+                .calculate_epoch_fee(Clock::get()?.epoch, amount)
+                .ok_or(PoolError::MathOverFlow)?;
+            Ok(fee)
+        }
+        Err(_) => Ok(0),
+    }
+}
+
+/// amount that arrives at the destination when `amount` is sent
+pub fn amount_received(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let fee = transfer_fee(mint, amount)?;
+    Ok(amount.checked_sub(fee).ok_or(PoolError::MathOverFlow)?)
+}
+
+/// amount that has to be sent so that exactly `amount` arrives at the destination
+pub fn amount_to_send(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID || amount == 0 {
+        return Ok(amount);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => {
+            let fee = config
+                .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+                .ok_or(PoolError::MathOverFlow)?;
+            Ok(amount.checked_add(fee).ok_or(PoolError::MathOverFlow)?)
+        }
+        Err(_) => Ok(amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::{entrypoint::SUCCESS, program_option::COption, program_pack::Pack, program_stubs::{set_syscall_stubs, SyscallStubs}};
+    use anchor_spl::{
+        token::spl_token,
+        token_2022::spl_token_2022::{
+            extension::{permanent_delegate::PermanentDelegate, transfer_fee::TransferFee, BaseStateWithExtensionsMut, StateWithExtensionsMut},
+            state::Mint,
+        },
+    };
+
+    /// `Clock::get` off chain, the default clock is at epoch 0
+    struct EpochZero;
+
+    impl SyscallStubs for EpochZero {
+        fn sol_get_clock_sysvar(&self, _var_addr: *mut u8) -> u64 {
+            SUCCESS
+        }
+    }
+
+    fn mint_data(extensions: &[ExtensionType], init: impl FnOnce(&mut StateWithExtensionsMut<Mint>)) -> Vec<u8> {
+        let mut data = vec![0; ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap()];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        init(&mut state);
+        state.base = Mint { mint_authority: COption::None, supply: 0, decimals: 6, is_initialized: true, freeze_authority: COption::None };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn transfer_fee_mint(fee_bps: u16, maximum_fee: u64) -> Vec<u8> {
+        mint_data(&[ExtensionType::TransferFeeConfig], |state| {
+            let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            let fee = TransferFee { epoch: 0.into(), maximum_fee: maximum_fee.into(), transfer_fee_basis_points: fee_bps.into() };
+            config.older_transfer_fee = fee;
+            config.newer_transfer_fee = fee;
+        })
+    }
+
+    fn permanent_delegate_mint() -> Vec<u8> {
+        mint_data(&[ExtensionType::PermanentDelegate], |state| {
+            let extension = state.init_extension::<PermanentDelegate>(true).unwrap();
+            extension.delegate = Some(Pubkey::new_unique()).try_into().unwrap();
+        })
+    }
+
+    fn with_mint<T>(owner: Pubkey, mut data: Vec<u8>, f: impl FnOnce(&AccountInfo) -> T) -> T {
+        set_syscall_stubs(Box::new(EpochZero));
+        let (key, mut lamports) = (Pubkey::new_unique(), 0);
+        f(&AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0))
+    }
+
+    #[test]
+    fn only_supported_extensions_pass_validation() {
+        with_mint(spl_token_2022::ID, transfer_fee_mint(100, u64::MAX), |mint| validate_mint(mint).unwrap());
+        with_mint(spl_token_2022::ID, mint_data(&[], |_| {}), |mint| validate_mint(mint).unwrap());
+        // classic mints have no extensions to check
+        with_mint(spl_token::ID, vec![0; spl_token::state::Mint::LEN], |mint| validate_mint(mint).unwrap());
+
+        let err = with_mint(spl_token_2022::ID, permanent_delegate_mint(), |mint| validate_mint(mint).unwrap_err());
+        assert_eq!(err, PoolError::UnsupportedMintExtension.into());
+    }
+
+    #[test]
+    fn transfer_fees_are_taken_off_and_grossed_up() {
+        with_mint(spl_token_2022::ID, transfer_fee_mint(100, u64::MAX), |mint| {
+            assert_eq!(amount_received(mint, 10_000).unwrap(), 9_900);
+            assert_eq!(amount_received(mint, 1).unwrap(), 0);
+            for amount in [1, 99, 9_900, 1_000_000_007] {
+                let sent = amount_to_send(mint, amount).unwrap();
+                assert!(sent > amount);
+                assert_eq!(amount_received(mint, sent).unwrap(), amount);
+            }
+            assert_eq!(amount_to_send(mint, 0).unwrap(), 0);
+        });
+
+        // past the cap the fee is flat
+        with_mint(spl_token_2022::ID, transfer_fee_mint(100, 50), |mint| {
+            assert_eq!(amount_received(mint, 1_000_000).unwrap(), 999_950);
+            assert_eq!(amount_to_send(mint, 1_000_000).unwrap(), 1_000_050);
+        });
+    }
+
+    #[test]
+    fn mints_without_a_transfer_fee_move_the_full_amount() {
+        with_mint(spl_token_2022::ID, permanent_delegate_mint(), |mint| {
+            assert_eq!(amount_received(mint, 10_000).unwrap(), 10_000);
+            assert_eq!(amount_to_send(mint, 10_000).unwrap(), 10_000);
+        });
+        with_mint(spl_token::ID, vec![0; spl_token::state::Mint::LEN], |mint| {
+            assert_eq!(amount_received(mint, 10_000).unwrap(), 10_000);
+            assert_eq!(amount_to_send(mint, 10_000).unwrap(), 10_000);
+        });
+    }
+}
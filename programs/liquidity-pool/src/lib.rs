@@ -1,12 +1,13 @@
 use anchor_lang::prelude::*;
 
-mod state;
+pub mod state;
+pub mod events;
 mod context;
-mod error;
+pub mod error;
+mod extensions;
+mod native_sol;
 
-use state::*;
 use context::*;
-use error::*;
 
 declare_id!("AH6xVywoqWvnPstLZVsvjYaaRFnQSLr8Dz2EbWgkAYx7");
 
@@ -14,17 +15,132 @@ declare_id!("AH6xVywoqWvnPstLZVsvjYaaRFnQSLr8Dz2EbWgkAYx7");
 pub mod liquidity_pool {
     use super::*;
 
-    pub fn deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<()> {
-        process_deposit(ctx, usdc_amount, wrapped_sol_amount)?;
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        process_initialize_config(ctx)?;
         Ok(())
     }
-    pub fn swap(ctx: Context<Swap> , swap_amount: u64) -> Result<()> {
-        process_swap(ctx, swap_amount)?;
+
+    pub fn add_fee_tier(ctx: Context<UpdateFeeTiers>, fee_tier: u16) -> Result<()> {
+        process_add_fee_tier(ctx, fee_tier)?;
+        Ok(())
+    }
+
+    pub fn remove_fee_tier(ctx: Context<UpdateFeeTiers>, fee_tier: u16) -> Result<()> {
+        process_remove_fee_tier(ctx, fee_tier)?;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, usdc_amount: u64, wrapped_sol_amount: u64, fee_tier: u16, native_sol: bool) -> Result<()> {
+        process_deposit(ctx, usdc_amount, wrapped_sol_amount, fee_tier, native_sol)?;
+        Ok(())
+    }
+    pub fn swap(ctx: Context<Swap> , swap_amount: u64, min_amount_out: u64, native_sol: bool) -> Result<()> {
+        process_swap(ctx, swap_amount, min_amount_out, native_sol)?;
+        Ok(())
+    }
+
+    pub fn swap_route<'info>(ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>, amount_in: u64, min_amount_out: u64, hops: u8) -> Result<()> {
+        process_swap_route(ctx, amount_in, min_amount_out, hops)?;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<WithDraw>, native_sol: bool) -> Result<()> {
+        process_withdraw(ctx, native_sol)?;
+        Ok(())
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashLoan>, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<()> {
+        process_flash_borrow(ctx, usdc_amount, wrapped_sol_amount)?;
+        Ok(())
+    }
+
+    pub fn flash_repay(ctx: Context<FlashLoan>) -> Result<()> {
+        process_flash_repay(ctx)?;
+        Ok(())
+    }
+
+    pub fn flash_swap<'info>(ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>, usdc_out: u64, wrapped_sol_out: u64, callback_data: Vec<u8>) -> Result<()> {
+        process_flash_swap(ctx, usdc_out, wrapped_sol_out, callback_data)?;
+        Ok(())
+    }
+
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        process_sync(ctx)?;
+        Ok(())
+    }
+
+    pub fn skim(ctx: Context<SkimReserves>) -> Result<()> {
+        process_skim(ctx)?;
+        Ok(())
+    }
+
+    pub fn migrate_legacy_pool(ctx: Context<MigrateLegacyPool>, fee_tier: u16) -> Result<()> {
+        process_migrate_legacy_pool(ctx, fee_tier)?;
+        Ok(())
+    }
+
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        process_migrate_position(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_dynamic_fee(ctx: Context<SetDynamicFee>, enabled: bool, min_fee_bps: u64, max_fee_bps: u64) -> Result<()> {
+        process_set_dynamic_fee(ctx, enabled, min_fee_bps, max_fee_bps)?;
+        Ok(())
+    }
+
+    pub fn initialize_rewards(ctx: Context<InitializeRewards>, emission_rate: u64, start_time: i64, end_time: i64) -> Result<()> {
+        process_initialize_rewards(ctx, emission_rate, start_time, end_time)?;
+        Ok(())
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        process_claim_rewards(ctx)?;
+        Ok(())
+    }
+
+    pub fn lock_position(ctx: Context<LockPosition>, duration: i64) -> Result<()> {
+        process_lock_position(ctx, duration)?;
+        Ok(())
+    }
+
+    pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+        process_expire_lock(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_compound_reward(ctx: Context<SetCompoundReward>, reward_bps: u64) -> Result<()> {
+        process_set_compound_reward(ctx, reward_bps)?;
+        Ok(())
+    }
+
+    pub fn compound_fees(ctx: Context<CompoundFees>) -> Result<()> {
+        process_compound_fees(ctx)?;
+        Ok(())
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        process_collect_fees(ctx)?;
+        Ok(())
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>, usdc_amount: u64, wrapped_sol_amount: u64) -> Result<()> {
+        process_open_position(ctx, usdc_amount, wrapped_sol_amount)?;
+        Ok(())
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        process_close_position(ctx)?;
+        Ok(())
+    }
+
+    pub fn collect_position_fees(ctx: Context<CollectPositionFees>) -> Result<()> {
+        process_collect_position_fees(ctx)?;
         Ok(())
     }
 
-    pub fn withdraw(ctx: Context<WithDraw>) -> Result<()> {
-        process_withdraw(ctx)?;
+    pub fn claim_position_rewards(ctx: Context<ClaimPositionRewards>) -> Result<()> {
+        process_claim_position_rewards(ctx)?;
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::{
+    token::spl_token,
+    token_2022::spl_token_2022,
+    token_interface::{close_account, sync_native, CloseAccount, SyncNative},
+};
+
+use crate::error::PoolError;
+
+pub fn require_native_mint(mint: &Pubkey) -> Result<()> {
+    require!(
+        *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID,
+        PoolError::NotNativeMint
+    );
+    Ok(())
+}
+
+/// moves `lamports` from the signer into a wsol account and syncs its token balance
+pub fn wrap_sol<'info>(
+    signer: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let transfer_accounts = Transfer {
+        from: signer,
+        to: token_account.clone(),
+    };
+    transfer(CpiContext::new(system_program, transfer_accounts), lamports)?;
+
+    let sync_accounts = SyncNative {
+        account: token_account,
+    };
+    sync_native(CpiContext::new(token_program, sync_accounts))?;
+
+    Ok(())
+}
+
+/// closes a wsol account owned by the signer, every lamport in it goes back to the signer
+pub fn unwrap_sol<'info>(
+    signer: AccountInfo<'info>,
+    token_account: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let close_accounts = CloseAccount {
+        account: token_account,
+        destination: signer.clone(),
+        authority: signer,
+    };
+    close_account(CpiContext::new(token_program, close_accounts))?;
+
+    Ok(())
+}
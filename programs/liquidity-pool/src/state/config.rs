@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_FEE_TIERS: usize = 8;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    #[max_len(MAX_FEE_TIERS)]
+    pub fee_tiers: Vec<u16>,
+    pub bump: u8
+}
@@ -1,6 +1,10 @@
 mod user;
 mod pool;
+mod config;
+mod reward;
 
 pub use pool::*;
 pub use user::*;
+pub use config::*;
+pub use reward::*;
 
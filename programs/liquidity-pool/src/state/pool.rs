@@ -1,14 +1,598 @@
 use anchor_lang::prelude::*;
+use bytemuck::Zeroable;
+use liquidity_pool_math as math;
 
+use crate::error::{math_error, DepositError, PoolError};
 
-#[account]
-#[derive(InitSpace)]
+pub use liquidity_pool_math::PRICE_SCALE;
+pub const VOLATILITY_WINDOW_SECS: i64 = 300;
+pub const MAX_FEE_BPS: u64 = 10_000;
+/// ceiling on the dynamic fee's upper bound, the fee is charged on top of the swap amount
+pub const MAX_DYNAMIC_FEE_BPS: u64 = 1_000;
+pub const FLASH_LOAN_FEE_BPS: u64 = 9;
+pub const MAX_ROUTE_HOPS: usize = 4;
+pub const ACCOUNTS_PER_HOP: usize = 5;
+pub const POOL_VERSION: u8 = 1;
+pub const MAX_COMPOUND_REWARD_BPS: u64 = 500;
+pub const POOL_RESERVED_BYTES: usize = 8;
+/// size of a baseline pool, the only layout from before zero-copy that exists on chain
+pub const LEGACY_POOL_LEN: usize = 8 + LegacyPool::INIT_SPACE;
+
+// a legacy pool must never be mistaken for a zero-copy one by its length
+const _: () = assert!(LEGACY_POOL_LEN < 8 + std::mem::size_of::<Pool>());
+
+/// fields are ordered by alignment so the struct has no implicit padding.
+/// flags are u8 (0 or 1) since bool isn't `Pod`.
+#[account(zero_copy)]
 pub struct Pool {
-    pub total_usdc_deposit: u64, 
-    pub total_sol_deposit: u64, 
-    pub fees_collected_usdc: u64, 
+    pub total_usdc_deposit: u64,
+    pub total_sol_deposit: u64,
+    pub fees_collected_usdc: u64,
+    pub fees_collected_sol: u64,
+    pub liquidity_fees: u64,
+    pub total_shares: u64,
+    pub min_fee_bps: u64,
+    pub max_fee_bps: u64,
+    pub reference_price: u64,
+    pub volatility_bps: u64,
+    pub last_update_ts: i64,
+    pub flash_borrowed_usdc: u64,
+    pub flash_borrowed_sol: u64,
+    pub authority: Pubkey,
+    pub fee_tier: u16,
+    pub bump: u8,
+    pub is_initialise: u8,
+    pub dynamic_fee_enabled: u8,
+    pub flash_loan_active: u8,
+    pub version: u8,
+    /// set once a reward vault exists, deposits and withdrawals then have to pass it
+    pub rewards_enabled: u8,
+    /// extra reward weight of locked positions on top of `total_shares`
+    pub boosted_shares: u64,
+    /// part of the fee buckets paid to whoever calls compound_fees
+    pub compound_reward_bps: u64,
+    /// shares of the baseline pool whose positions haven't gone through migrate_position yet
+    pub legacy_shares: u64,
+    /// fees per share ever added to each bucket, scaled by `GROWTH_SCALE`. stored as le bytes
+    /// since a u128 would change the struct's alignment.
+    pub fee_growth_usdc: [u8; 16],
+    pub fee_growth_sol: [u8; 16],
+    /// fee growth the last time a position moved its checkpoint, no checkpoint is above it
+    pub fee_growth_checkpoint_usdc: [u8; 16],
+    pub fee_growth_checkpoint_sol: [u8; 16],
+    pub reserved: [u8; POOL_RESERVED_BYTES]
+}
+
+/// the baseline borsh layout at [b"pool", usdc, sol], only read by migrate_legacy_pool
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyPool {
+    pub total_usdc_deposit: u64,
+    pub total_sol_deposit: u64,
+    pub fees_collected_usdc: u64,
     pub liquidity_fees: u64,
     pub total_shares: u64,
     pub bump: u8,
-    pub is_initialise: bool
+    pub is_initialise: bool,
+}
+
+impl Pool {
+    /// the legacy pool's reserves and shares. its fees are owed to the baseline positions,
+    /// which all migrate with a zero checkpoint, so they start the fee growth.
+    pub fn from_legacy(legacy: LegacyPool) -> Result<Self> {
+        let mut pool = Pool::zeroed();
+        pool.total_usdc_deposit = legacy.total_usdc_deposit;
+        pool.total_sol_deposit = legacy.total_sol_deposit;
+        pool.fees_collected_usdc = legacy.fees_collected_usdc;
+        pool.liquidity_fees = legacy.liquidity_fees;
+        pool.total_shares = legacy.total_shares;
+        pool.legacy_shares = legacy.total_shares;
+        pool.is_initialise = legacy.is_initialise as u8;
+        pool.version = POOL_VERSION;
+
+        let usdc_growth = math::growth_per_share(legacy.fees_collected_usdc as u128, legacy.total_shares).map_err(math_error)?;
+        pool.set_fee_growth(usdc_growth, 0);
+        // compounding must not sweep what the baseline positions are owed
+        pool.record_fee_checkpoint();
+        Ok(pool)
+    }
+
+    /// what rewards are shared by, every share plus the boost of locked positions
+    pub fn reward_weight(&self) -> Result<u64> {
+        Ok(self.total_shares.checked_add(self.boosted_shares).ok_or(DepositError::OverFlow)?)
+    }
+
+    /// usdc per sol, scaled by `PRICE_SCALE`
+    pub fn price(&self) -> Result<u64> {
+        let price = (self.total_usdc_deposit as u128)
+            .checked_mul(PRICE_SCALE)
+            .ok_or(PoolError::MathOverFlow)?
+            .checked_div(self.total_sol_deposit as u128)
+            .ok_or(PoolError::MathOverFlow)?;
+        Ok(price as u64)
+    }
+
+    /// fee in bps for a swap happening at `now`. with dynamic fees the base fee is
+    /// raised by the price movement seen inside the current window and clamped
+    /// to the configured bounds.
+    pub fn swap_fee_bps(&mut self, now: i64) -> Result<u64> {
+        if self.dynamic_fee_enabled == 0 {
+            return Ok(self.liquidity_fees);
+        }
+
+        if now.saturating_sub(self.last_update_ts) >= VOLATILITY_WINDOW_SECS || self.reference_price == 0 {
+            self.reference_price = self.price()?;
+            self.volatility_bps = 0;
+            self.last_update_ts = now;
+        }
+
+        let fee = self.liquidity_fees
+            .saturating_add(self.volatility_bps)
+            .clamp(self.min_fee_bps, self.max_fee_bps);
+        Ok(fee)
+    }
+
+    /// usdc -> sol leg, `usdc_amount` goes into the curve and the fee is paid on top of it.
+    /// returns (sol out, usdc fee) and updates the reserves.
+    pub fn swap_usdc_for_sol(&mut self, usdc_amount: u64, fee_bps: u64) -> Result<(u64, u64)> {
+        let outcome = math::swap_usdc_for_sol(self.total_usdc_deposit, self.total_sol_deposit, usdc_amount, fee_bps).map_err(math_error)?;
+        self.apply_swap(outcome)
+    }
+
+    /// sol -> usdc leg, the fee is taken out of the usdc leaving the curve.
+    /// returns (usdc out, usdc fee) and updates the reserves.
+    pub fn swap_sol_for_usdc(&mut self, sol_amount: u64, fee_bps: u64) -> Result<(u64, u64)> {
+        let outcome = math::swap_sol_for_usdc(self.total_usdc_deposit, self.total_sol_deposit, sol_amount, fee_bps).map_err(math_error)?;
+        self.apply_swap(outcome)
+    }
+
+    fn apply_swap(&mut self, outcome: math::SwapOutcome) -> Result<(u64, u64)> {
+        self.total_usdc_deposit = outcome.usdc_reserve;
+        self.total_sol_deposit = outcome.sol_reserve;
+        self.add_fees(outcome.fee, 0)?;
+
+        Ok((outcome.amount_out, outcome.fee))
+    }
+
+    /// mints shares for a deposit of already received amounts and adds it to the reserves,
+    /// see `liquidity_pool_math::deposit_shares` for how many.
+    pub fn add_liquidity(&mut self, usdc_amount: u64, sol_amount: u64) -> Result<u64> {
+        let shares = math::deposit_shares(self.total_usdc_deposit, self.total_sol_deposit, self.total_shares, usdc_amount, sol_amount)
+            .map_err(math_error)?;
+
+        self.total_shares = self.total_shares.checked_add(shares).ok_or(DepositError::OverFlow)?;
+        self.total_usdc_deposit = self.total_usdc_deposit.checked_add(usdc_amount).ok_or(DepositError::OverFlow)?;
+        self.total_sol_deposit = self.total_sol_deposit.checked_add(sol_amount).ok_or(DepositError::OverFlow)?;
+
+        Ok(shares)
+    }
+
+    /// burns `shares` and takes their pro rata part of the reserves out of the pool.
+    /// returns (usdc, sol) owed to the lp, rounded down. fees are paid by `User::collect_fees`.
+    pub fn remove_liquidity(&mut self, shares: u64) -> Result<(u64, u64)> {
+        let pro_rata = |amount: u64| math::pro_rata(amount, shares, self.total_shares).map_err(math_error);
+
+        let usdc = pro_rata(self.total_usdc_deposit)?;
+        let sol = pro_rata(self.total_sol_deposit)?;
+
+        self.total_shares = self.total_shares.checked_sub(shares).ok_or(DepositError::Underflow)?;
+        self.total_usdc_deposit = self.total_usdc_deposit.checked_sub(usdc).ok_or(DepositError::Underflow)?;
+        self.total_sol_deposit = self.total_sol_deposit.checked_sub(sol).ok_or(DepositError::Underflow)?;
+
+        Ok((usdc, sol))
+    }
+
+    /// (usdc, sol) fee growth per share
+    pub fn fee_growth(&self) -> (u128, u128) {
+        (u128::from_le_bytes(self.fee_growth_usdc), u128::from_le_bytes(self.fee_growth_sol))
+    }
+
+    fn set_fee_growth(&mut self, usdc: u128, sol: u128) {
+        self.fee_growth_usdc = usdc.to_le_bytes();
+        self.fee_growth_sol = sol.to_le_bytes();
+    }
+
+    /// (usdc, sol) fee growth at the latest position checkpoint
+    pub fn fee_growth_checkpoint(&self) -> (u128, u128) {
+        (u128::from_le_bytes(self.fee_growth_checkpoint_usdc), u128::from_le_bytes(self.fee_growth_checkpoint_sol))
+    }
+
+    /// called whenever a position moves its checkpoint up to the current growth
+    pub fn record_fee_checkpoint(&mut self) {
+        self.fee_growth_checkpoint_usdc = self.fee_growth_usdc;
+        self.fee_growth_checkpoint_sol = self.fee_growth_sol;
+    }
+
+    /// adds lp fees to the buckets and to the growth of every current share
+    pub fn add_fees(&mut self, usdc_fee: u64, sol_fee: u64) -> Result<()> {
+        self.fees_collected_usdc = self.fees_collected_usdc.checked_add(usdc_fee).ok_or(DepositError::OverFlow)?;
+        self.fees_collected_sol = self.fees_collected_sol.checked_add(sol_fee).ok_or(DepositError::OverFlow)?;
+
+        let (usdc_growth, sol_growth) = self.fee_growth();
+        let usdc_growth = usdc_growth
+            .checked_add(math::growth_per_share(usdc_fee as u128, self.total_shares).map_err(math_error)?)
+            .ok_or(DepositError::OverFlow)?;
+        let sol_growth = sol_growth
+            .checked_add(math::growth_per_share(sol_fee as u128, self.total_shares).map_err(math_error)?)
+            .ok_or(DepositError::OverFlow)?;
+        self.set_fee_growth(usdc_growth, sol_growth);
+        Ok(())
+    }
+
+    /// moves the fees every share has earned since the latest position checkpoint into the
+    /// reserves, after paying the keeper its bps of them. returns the (usdc, sol) added to the
+    /// reserves and the keeper's (usdc, sol).
+    /// fees from before that checkpoint are owed to some positions and not others, so they
+    /// stay in the buckets. growth drops back to the checkpoint, which leaves each position
+    /// exactly what it had pending before the swept growth.
+    /// adding the fees one sided ends at the same reserves as swapping half of them through
+    /// the curve and depositing both halves, so no swap is run.
+    pub fn compound_fees(&mut self, keeper_usdc_bps: u64, keeper_sol_bps: u64) -> Result<((u64, u64), (u64, u64))> {
+        let (usdc_growth, sol_growth) = self.fee_growth();
+        let (usdc_checkpoint, sol_checkpoint) = self.fee_growth_checkpoint();
+        // the shares haven't changed since the checkpoint, so this is covered by the buckets
+        let usdc = math::accrued(self.total_shares, usdc_growth, usdc_checkpoint).map_err(math_error)?;
+        let sol = math::accrued(self.total_shares, sol_growth, sol_checkpoint).map_err(math_error)?;
+        require!(usdc > 0 || sol > 0, PoolError::NoFeesToCompound);
+
+        let keeper_usdc = math::bps_of(usdc, keeper_usdc_bps).map_err(math_error)?;
+        let keeper_sol = math::bps_of(sol, keeper_sol_bps).map_err(math_error)?;
+        let (usdc_compounded, sol_compounded) = (usdc - keeper_usdc, sol - keeper_sol);
+
+        self.fees_collected_usdc = self.fees_collected_usdc.checked_sub(usdc).ok_or(DepositError::Underflow)?;
+        self.fees_collected_sol = self.fees_collected_sol.checked_sub(sol).ok_or(DepositError::Underflow)?;
+        self.total_usdc_deposit = self.total_usdc_deposit
+            .checked_add(usdc_compounded)
+            .ok_or(DepositError::OverFlow)?;
+        self.total_sol_deposit = self.total_sol_deposit
+            .checked_add(sol_compounded)
+            .ok_or(DepositError::OverFlow)?;
+        self.set_fee_growth(usdc_checkpoint, sol_checkpoint);
+
+        Ok(((usdc_compounded, sol_compounded), (keeper_usdc, keeper_sol)))
+    }
+
+    /// vaults have to hold at least the tracked reserves plus the fees owed to lps
+    pub fn check_vault_balances(&self, usdc_vault: u64, sol_vault: u64) -> Result<()> {
+        let usdc_claims = self.total_usdc_deposit.checked_add(self.fees_collected_usdc).ok_or(DepositError::OverFlow)?;
+        let sol_claims = self.total_sol_deposit.checked_add(self.fees_collected_sol).ok_or(DepositError::OverFlow)?;
+
+        require!(usdc_vault >= usdc_claims && sol_vault >= sol_claims, PoolError::InvariantViolated);
+        Ok(())
+    }
+
+    /// records the price movement against the window's reference price, called after reserves change
+    pub fn update_volatility(&mut self) -> Result<()> {
+        if self.dynamic_fee_enabled == 0 || self.reference_price == 0 {
+            return Ok(());
+        }
+
+        let price = self.price()?;
+        let movement_bps = (price.abs_diff(self.reference_price) as u128)
+            .checked_mul(MAX_FEE_BPS as u128)
+            .ok_or(PoolError::MathOverFlow)?
+            .checked_div(self.reference_price as u128)
+            .ok_or(PoolError::MathOverFlow)?;
+        self.volatility_bps = movement_bps.min(MAX_FEE_BPS as u128) as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use proptest::prelude::*;
+
+    use crate::state::User;
+
+    const MAX_RESERVE: u64 = 1 << 62;
+
+    fn seeded_pool(usdc: u64, sol: u64, fee_bps: u64) -> Pool {
+        let mut pool = Pool::zeroed();
+        pool.liquidity_fees = fee_bps;
+        pool.add_liquidity(usdc, sol).unwrap();
+        pool
+    }
+
+    fn position(total_shares: u64) -> User {
+        User { total_shares, ..Default::default() }
+    }
+
+    /// what deposit does to a position's shares and fee checkpoint
+    fn join(pool: &mut Pool, user: &mut User, usdc: u64, sol: u64) -> Result<u64> {
+        let (pending_usdc, pending_sol) = user.collect_fees(pool)?;
+        let shares = pool.add_liquidity(usdc, sol)?;
+        user.total_shares += shares;
+        user.carry_fees(pool, pending_usdc, pending_sol)?;
+        Ok(shares)
+    }
+
+    #[test]
+    fn removing_more_shares_than_the_pool_has_fails() {
+        let mut pool = seeded_pool(1 << 20, 1 << 20, 30);
+        let before = pool;
+
+        assert_eq!(pool.remove_liquidity(pool.total_shares + 1).unwrap_err(), DepositError::Underflow.into());
+        assert_eq!(bytemuck::bytes_of(&pool), bytemuck::bytes_of(&before));
+    }
+
+    #[test]
+    fn late_lps_only_earn_fees_from_after_they_joined() {
+        let mut pool = seeded_pool(1 << 20, 1 << 20, 30);
+        let mut alice = position(pool.total_shares);
+        pool.add_fees(3_000, 0).unwrap();
+
+        // bob matches alice's shares after the first fees
+        let mut bob = position(0);
+        join(&mut pool, &mut bob, 1 << 20, 1 << 20).unwrap();
+        assert_eq!(bob.total_shares, alice.total_shares);
+        pool.add_fees(2_000, 1_000).unwrap();
+
+        assert_eq!(bob.collect_fees(&mut pool).unwrap(), (1_000, 500));
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (4_000, 500));
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (0, 0));
+        assert_eq!((pool.fees_collected_usdc, pool.fees_collected_sol), (0, 0));
+
+        // topping up keeps what was pending
+        pool.add_fees(1_000, 0).unwrap();
+        join(&mut pool, &mut alice, 1 << 20, 1 << 20).unwrap();
+        pool.add_fees(1_500, 0).unwrap();
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (1_500, 0));
+        assert_eq!(bob.collect_fees(&mut pool).unwrap(), (1_000, 0));
+    }
+
+    #[test]
+    fn compounding_leaves_uncollected_fees_with_their_positions() {
+        let mut pool = seeded_pool(1 << 20, 1 << 20, 30);
+        let mut alice = position(pool.total_shares / 2);
+        let mut bob = position(pool.total_shares / 2);
+        pool.add_fees(2_000, 1_000).unwrap();
+
+        // alice collecting doesn't let a compound sweep bob's half into the reserves
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (1_000, 500));
+        assert!(pool.compound_fees(0, 0).is_err());
+
+        // only the fees both earned since are compounded
+        pool.add_fees(4_000, 0).unwrap();
+        let usdc_reserve = pool.total_usdc_deposit;
+        assert_eq!(pool.compound_fees(1_000, 1_000).unwrap(), ((3_600, 0), (400, 0)));
+        assert_eq!(pool.total_usdc_deposit, usdc_reserve + 3_600);
+        assert_eq!((pool.fees_collected_usdc, pool.fees_collected_sol), (1_000, 500));
+
+        assert_eq!(bob.collect_fees(&mut pool).unwrap(), (1_000, 500));
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (0, 0));
+
+        pool.add_fees(500, 0).unwrap();
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (250, 0));
+    }
+
+    #[test]
+    fn compounding_pays_the_keeper_its_cut_of_each_side() {
+        let mut pool = seeded_pool(1 << 20, 1 << 20, 30);
+        assert_eq!(pool.compound_fees(500, 500).unwrap_err(), PoolError::NoFeesToCompound.into());
+
+        pool.add_fees(10_000, 4_000).unwrap();
+        let (usdc_reserve, sol_reserve) = (pool.total_usdc_deposit, pool.total_sol_deposit);
+        // a side without a keeper account is passed 0 bps and compounded in full
+        assert_eq!(pool.compound_fees(500, 0).unwrap(), ((9_500, 4_000), (500, 0)));
+        assert_eq!(pool.total_usdc_deposit, usdc_reserve + 9_500);
+        assert_eq!(pool.total_sol_deposit, sol_reserve + 4_000);
+        assert_eq!((pool.fees_collected_usdc, pool.fees_collected_sol), (0, 0));
+        assert_eq!(pool.compound_fees(500, 500).unwrap_err(), PoolError::NoFeesToCompound.into());
+
+        pool.add_fees(0, 2_000).unwrap();
+        assert_eq!(pool.compound_fees(MAX_COMPOUND_REWARD_BPS, MAX_COMPOUND_REWARD_BPS).unwrap(), ((0, 1_900), (0, 100)));
+    }
+
+    #[test]
+    fn baseline_fees_go_to_the_migrated_positions() {
+        let legacy = LegacyPool {
+            total_usdc_deposit: 1 << 20,
+            total_sol_deposit: 1 << 20,
+            fees_collected_usdc: 4_000,
+            liquidity_fees: 30,
+            total_shares: 1 << 20,
+            bump: 255,
+            is_initialise: true,
+        };
+        let mut pool = Pool::from_legacy(legacy).unwrap();
+        let (mut alice, mut bob) = (position(pool.total_shares / 4), position(pool.total_shares - pool.total_shares / 4));
+
+        // only fees from after the migration can be compounded
+        assert!(pool.compound_fees(0, 0).is_err());
+        assert_eq!(alice.collect_fees(&mut pool).unwrap(), (1_000, 0));
+        assert_eq!(bob.collect_fees(&mut pool).unwrap(), (3_000, 0));
+    }
+
+    fn reserve_product(pool: &Pool) -> u128 {
+        pool.total_usdc_deposit as u128 * pool.total_sol_deposit as u128
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Deposit(u64, usize),
+        SwapUsdc(u64),
+        SwapSol(u64),
+        Withdraw(usize),
+        Compound(u64),
+        Collect(usize),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (1u64..1_000_000_000_000, any::<usize>()).prop_map(|(amount, index)| Op::Deposit(amount, index)),
+            (1u64..1_000_000_000_000).prop_map(Op::SwapUsdc),
+            (1u64..1_000_000_000_000).prop_map(Op::SwapSol),
+            any::<usize>().prop_map(Op::Withdraw),
+            (0u64..=MAX_COMPOUND_REWARD_BPS).prop_map(Op::Compound),
+            any::<usize>().prop_map(Op::Collect),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn usdc_for_sol_never_shrinks_k(
+            usdc in 1_000u64..MAX_RESERVE,
+            sol in 1_000u64..MAX_RESERVE,
+            amount in 1u64..u64::MAX,
+            fee_bps in 0u64..=MAX_FEE_BPS
+        ) {
+            let mut pool = seeded_pool(usdc, sol, fee_bps);
+            let k = reserve_product(&pool);
+
+            if let Ok((sol_out, fee)) = pool.swap_usdc_for_sol(amount, fee_bps) {
+                prop_assert!(reserve_product(&pool) >= k);
+                prop_assert!(sol_out < sol);
+                prop_assert_eq!(pool.fees_collected_usdc, fee);
+            }
+        }
+
+        #[test]
+        fn sol_for_usdc_never_shrinks_k(
+            usdc in 1_000u64..MAX_RESERVE,
+            sol in 1_000u64..MAX_RESERVE,
+            amount in 1u64..u64::MAX,
+            fee_bps in 0u64..=MAX_FEE_BPS
+        ) {
+            let mut pool = seeded_pool(usdc, sol, fee_bps);
+            let k = reserve_product(&pool);
+
+            if let Ok((usdc_out, fee)) = pool.swap_sol_for_usdc(amount, fee_bps) {
+                prop_assert!(reserve_product(&pool) >= k);
+                prop_assert!(usdc_out + fee < usdc);
+                prop_assert_eq!(pool.fees_collected_usdc, fee);
+            }
+        }
+
+        #[test]
+        fn deposit_then_withdraw_never_returns_more(
+            usdc in 1_000u64..MAX_RESERVE,
+            sol in 1_000u64..MAX_RESERVE,
+            deposit_usdc in 1u64..MAX_RESERVE
+        ) {
+            let mut pool = seeded_pool(usdc, sol, 30);
+            let deposit_sol = (deposit_usdc as u128 * sol as u128).div_ceil(usdc as u128);
+            prop_assume!(deposit_sol <= MAX_RESERVE as u128);
+            let deposit_sol = deposit_sol as u64;
+
+            if let Ok(shares) = pool.add_liquidity(deposit_usdc, deposit_sol) {
+                let (usdc_out, sol_out) = pool.remove_liquidity(shares).unwrap();
+                prop_assert!(usdc_out <= deposit_usdc);
+                prop_assert!(sol_out <= deposit_sol);
+            }
+        }
+
+        #[test]
+        fn shares_are_never_minted_for_nothing(
+            usdc in 1_000u64..MAX_RESERVE,
+            sol in 1_000u64..MAX_RESERVE,
+            deposit_usdc in 0u64..1_000_000,
+            deposit_sol in 0u64..1_000_000
+        ) {
+            prop_assert!(Pool::zeroed().add_liquidity(deposit_usdc, 0).is_err());
+            prop_assert!(Pool::zeroed().add_liquidity(0, deposit_sol).is_err());
+
+            let mut pool = seeded_pool(usdc, sol, 30);
+            if let Ok(shares) = pool.add_liquidity(deposit_usdc, deposit_sol) {
+                prop_assert!(shares > 0);
+                // every minted share is backed by at least its pro rata value on both sides
+                prop_assert!(shares as u128 * usdc as u128 <= deposit_usdc as u128 * (pool.total_shares - shares) as u128);
+                prop_assert!(shares as u128 * sol as u128 <= deposit_sol as u128 * (pool.total_shares - shares) as u128);
+            }
+        }
+
+        #[test]
+        fn single_lp_withdraws_everything_at_extremes(
+            usdc in 1u64..=u64::MAX,
+            sol in 1u64..=u64::MAX,
+            fees_usdc in 0u64..=u64::MAX,
+            fees_sol in 0u64..=u64::MAX
+        ) {
+            let mut pool = seeded_pool(usdc, sol, 30);
+            let mut lp = position(pool.total_shares);
+            pool.add_fees(fees_usdc, fees_sol).unwrap();
+
+            // growth per share rounds down, which can leave one unit in each bucket
+            let (usdc_fee, sol_fee) = lp.collect_fees(&mut pool).unwrap();
+            prop_assert!(fees_usdc - usdc_fee <= 1 && fees_sol - sol_fee <= 1);
+            prop_assert_eq!(pool.remove_liquidity(lp.total_shares).unwrap(), (usdc, sol));
+            prop_assert_eq!(pool.total_shares, 0);
+        }
+
+        #[test]
+        fn vaults_cover_claims_across_operations(
+            usdc in 1_000_000u64..1_000_000_000_000_000,
+            sol in 1_000_000u64..1_000_000_000_000_000,
+            fee_bps in 0u64..=1_000,
+            ops in prop::collection::vec(op(), 1..40)
+        ) {
+            let mut pool = seeded_pool(usdc, sol, fee_bps);
+            let mut positions = vec![position(pool.total_shares)];
+            let (mut vault_usdc, mut vault_sol) = (usdc, sol);
+
+            for op in ops {
+                match op {
+                    Op::Deposit(amount, index) => {
+                        if pool.total_usdc_deposit == 0 {
+                            continue;
+                        }
+                        let sol_amount = (amount as u128 * pool.total_sol_deposit as u128).div_ceil(pool.total_usdc_deposit as u128) as u64;
+                        // tops up an existing position or opens a new one, a failed deposit reverts
+                        let index = index % (positions.len() + 1);
+                        let (mut next, mut lp) = (pool, positions.get(index).cloned().unwrap_or_else(|| position(0)));
+                        if join(&mut next, &mut lp, amount, sol_amount).is_ok() {
+                            pool = next;
+                            if index == positions.len() {
+                                positions.push(lp);
+                            } else {
+                                positions[index] = lp;
+                            }
+                            vault_usdc += amount;
+                            vault_sol += sol_amount;
+                        }
+                    }
+                    Op::SwapUsdc(amount) => {
+                        if let Ok((sol_out, fee)) = pool.swap_usdc_for_sol(amount, fee_bps) {
+                            vault_usdc += amount + fee;
+                            vault_sol -= sol_out;
+                        }
+                    }
+                    Op::SwapSol(amount) => {
+                        if let Ok((usdc_out, _)) = pool.swap_sol_for_usdc(amount, fee_bps) {
+                            vault_sol += amount;
+                            vault_usdc -= usdc_out;
+                        }
+                    }
+                    Op::Withdraw(index) => {
+                        if positions.is_empty() {
+                            continue;
+                        }
+                        let mut lp = positions.swap_remove(index % positions.len());
+                        let (usdc_fee, sol_fee) = lp.collect_fees(&mut pool).unwrap();
+                        let (usdc_out, sol_out) = pool.remove_liquidity(lp.total_shares).unwrap();
+                        vault_usdc -= usdc_out + usdc_fee;
+                        vault_sol -= sol_out + sol_fee;
+                    }
+                    Op::Compound(keeper_bps) => {
+                        if let Ok((_, (keeper_usdc, keeper_sol))) = pool.compound_fees(keeper_bps, keeper_bps) {
+                            vault_usdc -= keeper_usdc;
+                            vault_sol -= keeper_sol;
+                        }
+                    }
+                    Op::Collect(index) => {
+                        if positions.is_empty() {
+                            continue;
+                        }
+                        let index = index % positions.len();
+                        let (usdc_fee, sol_fee) = positions[index].collect_fees(&mut pool).unwrap();
+                        vault_usdc -= usdc_fee;
+                        vault_sol -= sol_fee;
+                    }
+                }
+
+                prop_assert!(pool.check_vault_balances(vault_usdc, vault_sol).is_ok());
+                prop_assert_eq!(positions.iter().map(|lp| lp.total_shares).sum::<u64>(), pool.total_shares);
+            }
+        }
+    }
 }
\ No newline at end of file
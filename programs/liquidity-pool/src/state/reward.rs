@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use liquidity_pool_math as math;
+
+use crate::error::{math_error, DepositError};
+
+/// liquidity mining for one pool. `emission_rate` reward tokens per second between the
+/// start and end time are shared by all shares, the tokens sit in the vault's own ata.
+#[account]
+#[derive(InitSpace)]
+pub struct RewardVault {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub emission_rate: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub last_update_time: i64,
+    /// rewards per share emitted so far, scaled by `GROWTH_SCALE`
+    pub reward_per_share: u128,
+    pub bump: u8,
+}
+
+impl RewardVault {
+    /// everything the schedule pays out
+    pub fn total_emissions(&self) -> Result<u64> {
+        let duration = self.end_time.checked_sub(self.start_time).ok_or(DepositError::Underflow)? as u64;
+        Ok(duration.checked_mul(self.emission_rate).ok_or(DepositError::OverFlow)?)
+    }
+
+    /// accrues emissions up to `now` to the shares held until then, has to run before the
+    /// pool's total shares change
+    pub fn accrue(&mut self, total_shares: u64, now: i64) -> Result<()> {
+        let until = now.min(self.end_time);
+        if until <= self.last_update_time {
+            return Ok(());
+        }
+
+        let emitted = ((until - self.last_update_time) as u128)
+            .checked_mul(self.emission_rate as u128)
+            .ok_or(DepositError::OverFlow)?;
+        let growth = math::growth_per_share(emitted, total_shares).map_err(math_error)?;
+
+        self.reward_per_share = self.reward_per_share.checked_add(growth).ok_or(DepositError::OverFlow)?;
+        self.last_update_time = until;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use crate::state::{lock_boost_bps, Pool, User, MAX_LOCK_SECS};
+
+    fn vault() -> RewardVault {
+        RewardVault {
+            pool: Pubkey::default(),
+            reward_mint: Pubkey::default(),
+            emission_rate: 10,
+            start_time: 100,
+            end_time: 200,
+            last_update_time: 100,
+            reward_per_share: 0,
+            bump: 0,
+        }
+    }
+
+    fn user(total_shares: u64) -> User {
+        User { total_shares, ..Default::default() }
+    }
+
+    #[test]
+    fn emissions_are_shared_by_the_shares_held_at_the_time() {
+        let mut vault = vault();
+        let (mut alice, mut bob) = (user(100), user(0));
+
+        // nothing before the start
+        vault.accrue(100, 50).unwrap();
+        assert_eq!(vault.reward_per_share, 0);
+
+        // alice alone for 50 seconds, then bob joins with as many shares
+        vault.accrue(100, 150).unwrap();
+        bob.settle_rewards(&vault).unwrap();
+        bob.total_shares = 100;
+
+        // emissions stop at the end time
+        vault.accrue(200, 1_000).unwrap();
+        alice.settle_rewards(&vault).unwrap();
+        bob.settle_rewards(&vault).unwrap();
+
+        assert_eq!((alice.rewards_owed, bob.rewards_owed), (750, 250));
+        assert!(alice.rewards_owed + bob.rewards_owed <= vault.total_emissions().unwrap());
+
+        // settling twice pays nothing new
+        alice.settle_rewards(&vault).unwrap();
+        assert_eq!(alice.rewards_owed, 750);
+    }
+
+    #[test]
+    fn locked_positions_earn_their_boost_until_touched_after_expiry() {
+        let mut vault = vault();
+        let mut pool = Pool::zeroed();
+        pool.total_shares = 200;
+        let (mut alice, mut bob) = (user(100), user(100));
+
+        // alice locks for the max duration at 2x
+        alice.locked_until = 150;
+        alice.lock_boost_bps = lock_boost_bps(MAX_LOCK_SECS).unwrap();
+        alice.refresh_boost(&mut pool, 100).unwrap();
+        assert_eq!((alice.boost_shares, pool.reward_weight().unwrap()), (100, 300));
+
+        vault.accrue(pool.reward_weight().unwrap(), 150).unwrap();
+        alice.settle_rewards(&vault).unwrap();
+        bob.settle_rewards(&vault).unwrap();
+        assert_eq!((alice.rewards_owed, bob.rewards_owed), (333, 166));
+
+        // the expired boost is dropped from the pool on the next touch
+        alice.refresh_boost(&mut pool, 150).unwrap();
+        assert_eq!((alice.boost_shares, pool.boosted_shares), (0, 0));
+        assert!(lock_boost_bps(0).is_err());
+    }
+}
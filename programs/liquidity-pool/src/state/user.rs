@@ -1,10 +1,126 @@
 use anchor_lang::prelude::*;
+use liquidity_pool_math as math;
+
+use crate::{error::{math_error, DepositError, PoolError}, state::{Pool, RewardVault}};
+
+pub const USER_VERSION: u8 = 1;
+pub const USER_RESERVED_BYTES: usize = 24;
+/// size of a baseline position, every later layout is longer
+pub const LEGACY_USER_LEN: usize = 8 + LegacyUser::INIT_SPACE;
+pub const MIN_LOCK_SECS: i64 = 86_400;
+pub const MAX_LOCK_SECS: i64 = 365 * 86_400;
+/// extra reward weight of a position locked for `MAX_LOCK_SECS`, shorter locks scale linearly
+pub const MAX_LOCK_BOOST_BPS: u64 = 10_000;
 
 #[account]
-#[derive(InitSpace)]
+#[derive(InitSpace, Default)]
 pub struct User {
     pub owner: Pubkey,
     pub usdc_deposit: u64, 
     pub sol_deposit: u64, 
     pub total_shares: u64,
-}
\ No newline at end of file
+    pub version: u8,
+    /// reward vault accumulator at the last settlement
+    pub reward_per_share_paid: u128,
+    /// settled rewards not claimed yet
+    pub rewards_owed: u64,
+    /// shares can't be withdrawn before this unix time
+    pub locked_until: i64,
+    /// reward boost of the current lock, in bps of the shares
+    pub lock_boost_bps: u64,
+    /// extra reward weight this position adds to `Pool::boosted_shares`
+    pub boost_shares: u64,
+    /// pool fee growth at the last collection
+    pub fee_growth_inside_last_usdc: u128,
+    pub fee_growth_inside_last_sol: u128,
+    /// pool the shares are in, also part of the position's seeds
+    pub pool: Pubkey,
+    pub reserved: [u8; USER_RESERVED_BYTES],
+}
+
+/// the baseline borsh layout at [b"lp", owner], only read by migrate_position
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyUser {
+    pub owner: Pubkey,
+    pub usdc_deposit: u64,
+    pub sol_deposit: u64,
+    pub total_shares: u64,
+}
+
+/// reward boost for locking `duration` seconds
+pub fn lock_boost_bps(duration: i64) -> Result<u64> {
+    require!((MIN_LOCK_SECS..=MAX_LOCK_SECS).contains(&duration), PoolError::InvalidLockDuration);
+    Ok(MAX_LOCK_BOOST_BPS * duration as u64 / MAX_LOCK_SECS as u64)
+}
+
+impl User {
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.locked_until
+    }
+
+    /// shares plus the lock boost
+    pub fn reward_weight(&self) -> Result<u64> {
+        Ok(self.total_shares.checked_add(self.boost_shares).ok_or(DepositError::OverFlow)?)
+    }
+
+    /// moves what the position earned since its last checkpoint into `rewards_owed`,
+    /// the vault has to be accrued first
+    pub fn settle_rewards(&mut self, reward_vault: &RewardVault) -> Result<()> {
+        let earned = math::accrued(self.reward_weight()?, reward_vault.reward_per_share, self.reward_per_share_paid).map_err(math_error)?;
+        self.rewards_owed = self.rewards_owed.checked_add(earned).ok_or(DepositError::OverFlow)?;
+        self.reward_per_share_paid = reward_vault.reward_per_share;
+        Ok(())
+    }
+
+    /// recomputes the boost for the current shares and moves the difference into the pool's
+    /// total. an expired lock keeps its boost until the position is touched again or someone
+    /// calls expire_lock, so this runs after every settlement.
+    pub fn refresh_boost(&mut self, pool: &mut Pool, now: i64) -> Result<()> {
+        let boost_shares = if self.is_locked(now) {
+            math::bps_of(self.total_shares, self.lock_boost_bps).map_err(math_error)?
+        } else {
+            0
+        };
+
+        pool.boosted_shares = pool.boosted_shares
+            .checked_sub(self.boost_shares)
+            .ok_or(DepositError::Underflow)?
+            .checked_add(boost_shares)
+            .ok_or(DepositError::OverFlow)?;
+        self.boost_shares = boost_shares;
+        Ok(())
+    }
+
+    /// takes the fees earned since the last checkpoint out of the pool's buckets and moves the
+    /// checkpoint up. returns the (usdc, sol) the position has to be paid.
+    pub fn collect_fees(&mut self, pool: &mut Pool) -> Result<(u64, u64)> {
+        let (usdc_growth, sol_growth) = pool.fee_growth();
+        let usdc = math::accrued(self.total_shares, usdc_growth, self.fee_growth_inside_last_usdc).map_err(math_error)?;
+        let sol = math::accrued(self.total_shares, sol_growth, self.fee_growth_inside_last_sol).map_err(math_error)?;
+        pool.fees_collected_usdc = pool.fees_collected_usdc.checked_sub(usdc).ok_or(DepositError::Underflow)?;
+        pool.fees_collected_sol = pool.fees_collected_sol.checked_sub(sol).ok_or(DepositError::Underflow)?;
+
+        self.fee_growth_inside_last_usdc = usdc_growth;
+        self.fee_growth_inside_last_sol = sol_growth;
+        pool.record_fee_checkpoint();
+        Ok((usdc, sol))
+    }
+
+    /// puts fees taken by `collect_fees` back into the buckets and sets the checkpoint so the
+    /// current shares can collect them later. deposits use this around changing the shares.
+    pub fn carry_fees(&mut self, pool: &mut Pool, usdc: u64, sol: u64) -> Result<()> {
+        pool.fees_collected_usdc = pool.fees_collected_usdc.checked_add(usdc).ok_or(DepositError::OverFlow)?;
+        pool.fees_collected_sol = pool.fees_collected_sol.checked_add(sol).ok_or(DepositError::OverFlow)?;
+
+        // rounds the carried growth down, so at most dust is left behind
+        let (usdc_growth, sol_growth) = pool.fee_growth();
+        let carried = |amount: u64, growth: u128| -> Result<u128> {
+            let per_share = math::growth_per_share(amount as u128, self.total_shares).map_err(math_error)?;
+            Ok(growth.checked_sub(per_share).ok_or(DepositError::Underflow)?)
+        };
+        self.fee_growth_inside_last_usdc = carried(usdc, usdc_growth)?;
+        self.fee_growth_inside_last_sol = carried(sol, sol_growth)?;
+        pool.record_fee_checkpoint();
+        Ok(())
+    }
+}
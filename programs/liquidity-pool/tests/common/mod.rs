@@ -0,0 +1,710 @@
+// shared between the integration test binaries, each one only uses part of it
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    solana_program::{
+        bpf_loader_upgradeable,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        program_pack::Pack,
+        system_instruction, system_program,
+    },
+    AccountDeserialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address, spl_associated_token_account},
+    token::spl_token,
+    token_2022,
+};
+use liquidity_pool::state::{Pool, User};
+use litesvm::{types::TransactionResult, LiteSVM};
+use solana_account::Account;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+/// built by `anchor build`. the tests using it are `#[ignore]`d, run them with
+/// `anchor run test-sbf` so the binary is rebuilt first
+pub const PROGRAM_SO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/liquidity_pool.so");
+pub const FEE_TIER: u16 = 30;
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const USDC_DECIMALS: u8 = 6;
+pub const SOL_DECIMALS: u8 = 9;
+
+pub struct TestPool {
+    pub svm: LiteSVM,
+    pub payer: Keypair,
+    pub usdc_mint: Pubkey,
+    pub wrapped_sol_mint: Pubkey,
+    pub config: Pubkey,
+    /// the pool the instruction builders target, see `use_fee_tier`
+    pub fee_tier: u16,
+    pub pool: Pubkey,
+    pub pool_usdc_ata: Pubkey,
+    pub pool_sol_ata: Pubkey,
+    /// the `native_sol` flag deposit, swap and withdraw are built with
+    pub native_sol: bool,
+}
+
+impl TestPool {
+    /// fresh svm with the program loaded, two classic spl mints and the global config.
+    /// panics when the program hasn't been built rather than passing without running.
+    pub fn new() -> Self {
+        let mut test_pool = TestPool::without_config();
+        let ix = test_pool.initialize_config_ix(&test_pool.payer.pubkey());
+        test_pool.send(ix, &[]).unwrap();
+        test_pool
+    }
+
+    /// like `new` but the pool's sol side is the spl native mint, users hold lamports
+    /// rather than wsol so `new_user` has to be given no sol
+    pub fn with_native_sol() -> Self {
+        let mut test_pool = TestPool::new();
+        let native_mint = spl_token::state::Mint {
+            decimals: SOL_DECIMALS,
+            is_initialized: true,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        native_mint.pack_into_slice(&mut data);
+        let lamports = test_pool.svm.minimum_balance_for_rent_exemption(data.len());
+        let account = Account { lamports, data, owner: spl_token::ID, executable: false, rent_epoch: 0 };
+        test_pool.svm.set_account(spl_token::native_mint::ID, account).unwrap();
+
+        test_pool.wrapped_sol_mint = spl_token::native_mint::ID;
+        test_pool.native_sol = true;
+        test_pool.use_fee_tier(FEE_TIER);
+        test_pool
+    }
+
+    /// like `new` but the config isn't created yet, the payer is the upgrade authority
+    pub fn without_config() -> Self {
+        TestPool::with_program(PROGRAM_SO)
+    }
+
+    /// `without_config` running the build at `program_so`
+    pub fn with_program(program_so: &str) -> Self {
+        let program = std::fs::read(program_so).unwrap_or_else(|err| panic!("{program_so}: {err}, run `anchor build` first"));
+
+        let mut svm = LiteSVM::new();
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 1_000 * LAMPORTS_PER_SOL).unwrap();
+        deploy_upgradeable(&mut svm, &program, &payer.pubkey());
+
+        let usdc_mint = create_mint(&mut svm, &payer, USDC_DECIMALS);
+        let wrapped_sol_mint = create_mint(&mut svm, &payer, SOL_DECIMALS);
+
+        let (config, _) = Pubkey::find_program_address(&[b"config"], &liquidity_pool::ID);
+
+        let mut test_pool = TestPool {
+            svm,
+            payer,
+            usdc_mint,
+            wrapped_sol_mint,
+            config,
+            fee_tier: 0,
+            pool: Pubkey::default(),
+            pool_usdc_ata: Pubkey::default(),
+            pool_sol_ata: Pubkey::default(),
+            native_sol: false,
+        };
+        test_pool.use_fee_tier(FEE_TIER);
+        test_pool
+    }
+
+    pub fn initialize_config_ix(&self, admin: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::InitializeConfig {
+                admin: *admin,
+                config: self.config,
+                program_data: program_data_address(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::InitializeConfig {}.data(),
+        }
+    }
+
+    /// points the instruction builders at the pool of the same mints on `fee_tier`
+    pub fn use_fee_tier(&mut self, fee_tier: u16) {
+        self.fee_tier = fee_tier;
+        self.pool = Pubkey::find_program_address(
+            &[b"pool", self.usdc_mint.as_ref(), self.wrapped_sol_mint.as_ref(), fee_tier.to_le_bytes().as_ref()],
+            &liquidity_pool::ID,
+        )
+        .0;
+        self.pool_usdc_ata = get_associated_token_address(&self.pool, &self.usdc_mint);
+        self.pool_sol_ata = get_associated_token_address(&self.pool, &self.wrapped_sol_mint);
+    }
+
+    /// classic spl mint with the payer as its authority
+    pub fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        create_mint(&mut self.svm, &self.payer, decimals)
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn warp(&mut self, secs: i64) {
+        let mut clock = self.svm.get_sysvar::<Clock>();
+        clock.unix_timestamp += secs;
+        self.svm.set_sysvar(&clock);
+    }
+
+    /// sends a single instruction paid by the payer, `signers` are any extra signers
+    #[allow(clippy::result_large_err)]
+    pub fn send(&mut self, ix: Instruction, signers: &[&Keypair]) -> TransactionResult {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+
+        let tx = Transaction::new_signed_with_payer(&[ix], Some(&self.payer.pubkey()), &all_signers, self.svm.latest_blockhash());
+        let result = self.svm.send_transaction(tx);
+        // identical transactions later in a test would otherwise be rejected as duplicates
+        self.svm.expire_blockhash();
+        result
+    }
+
+    /// funded user with both atas holding `usdc` and `sol` base units
+    pub fn new_user(&mut self, usdc: u64, sol: u64) -> Keypair {
+        let user = Keypair::new();
+        self.svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+
+        let (usdc_mint, wrapped_sol_mint) = (self.usdc_mint, self.wrapped_sol_mint);
+        self.fund(&user.pubkey(), &usdc_mint, usdc);
+        self.fund(&user.pubkey(), &wrapped_sol_mint, sol);
+
+        user
+    }
+
+    /// creates `owner`'s ata of one of the payer's mints and mints `amount` into it
+    pub fn fund(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let ata = get_associated_token_address(owner, mint);
+        let create_ata =
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(&self.payer.pubkey(), owner, mint, &spl_token::ID);
+        self.send(create_ata, &[]).unwrap();
+
+        if amount > 0 {
+            let mint_to = spl_token::instruction::mint_to(&spl_token::ID, mint, &ata, &self.payer.pubkey(), &[], amount).unwrap();
+            self.send(mint_to, &[]).unwrap();
+        }
+
+        ata
+    }
+
+    /// the user's position in the current pool
+    pub fn user_pda(&self, user: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"lp", self.pool.as_ref(), user.as_ref()], &liquidity_pool::ID).0
+    }
+
+    /// None until the first deposit creates the pool
+    pub fn pool_state(&self) -> Option<Pool> {
+        let account = self.svm.get_account(&self.pool)?;
+        let data = account.data.get(8..8 + std::mem::size_of::<Pool>())?;
+        Some(bytemuck::pod_read_unaligned(data))
+    }
+
+    /// overwrites the pool account, for states the instructions can't reach on their own
+    pub fn set_pool_state(&mut self, state: &Pool) {
+        let mut account = self.svm.get_account(&self.pool).unwrap();
+        account.data[8..8 + std::mem::size_of::<Pool>()].copy_from_slice(bytemuck::bytes_of(state));
+        self.svm.set_account(self.pool, account).unwrap();
+    }
+
+    /// None when the user has no open position
+    pub fn user_state(&self, user: &Pubkey) -> Option<User> {
+        let account = self.svm.get_account(&self.user_pda(user))?;
+        User::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_account(address).map_or(0, |account| account.lamports)
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        let account = self.svm.get_account(token_account).unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    pub fn mint_state(&self, mint: &Pubkey) -> spl_token::state::Mint {
+        let account = self.svm.get_account(mint).unwrap();
+        spl_token::state::Mint::unpack(&account.data).unwrap()
+    }
+
+    pub fn position_pda(&self, position_mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"position", self.pool.as_ref(), position_mint.as_ref()], &liquidity_pool::ID).0
+    }
+
+    /// None once the position is closed
+    pub fn position_state(&self, position_mint: &Pubkey) -> Option<User> {
+        let account = self.svm.get_account(&self.position_pda(position_mint))?;
+        User::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    /// moves the position's token from `holder`'s ata to `to`'s, creating it if needed
+    pub fn transfer_position(&mut self, holder: &Keypair, to: &Pubkey, position_mint: &Pubkey) {
+        let create_ata =
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(&self.payer.pubkey(), to, position_mint, &spl_token::ID);
+        self.send(create_ata, &[]).unwrap();
+
+        let transfer = spl_token::instruction::transfer_checked(
+            &spl_token::ID,
+            &get_associated_token_address(&holder.pubkey(), position_mint),
+            position_mint,
+            &get_associated_token_address(to, position_mint),
+            &holder.pubkey(),
+            &[],
+            1,
+            0,
+        )
+        .unwrap();
+        self.send(transfer, &[holder]).unwrap();
+    }
+
+    pub fn deposit_ix(&self, user: &Pubkey, usdc_amount: u64, wrapped_sol_amount: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::Deposit {
+                signer: *user,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                user_usdc_ata: get_associated_token_address(user, &self.usdc_mint),
+                user_sol_ata: get_associated_token_address(user, &self.wrapped_sol_mint),
+                user_pda: self.user_pda(user),
+                pool_pda: self.pool,
+                config: self.config,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                associated_token_program: associated_token::ID,
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                system_program: system_program::ID,
+                reward_vault: None,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::Deposit {
+                usdc_amount,
+                wrapped_sol_amount,
+                fee_tier: self.fee_tier,
+                native_sol: self.native_sol,
+            }
+            .data(),
+        }
+    }
+
+    /// `usdc_in` swaps usdc for sol, otherwise sol for usdc
+    pub fn swap_ix(&self, user: &Pubkey, swap_amount: u64, usdc_in: bool) -> Instruction {
+        self.swap_min_out_ix(user, swap_amount, 0, usdc_in)
+    }
+
+    pub fn swap_min_out_ix(&self, user: &Pubkey, swap_amount: u64, min_amount_out: u64, usdc_in: bool) -> Instruction {
+        let (quote_mint, base_mint) = if usdc_in {
+            (self.usdc_mint, self.wrapped_sol_mint)
+        } else {
+            (self.wrapped_sol_mint, self.usdc_mint)
+        };
+
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::Swap {
+                signer: *user,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                user_quote_ata: get_associated_token_address(user, &quote_mint),
+                user_base_ata: get_associated_token_address(user, &base_mint),
+                base_mint,
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                base_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::Swap { swap_amount, min_amount_out, native_sol: self.native_sol }.data(),
+        }
+    }
+
+    /// the remaining accounts of a swap_route hop through the current pool
+    pub fn route_hop(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.usdc_mint, false),
+            AccountMeta::new_readonly(self.wrapped_sol_mint, false),
+            AccountMeta::new(self.pool_usdc_ata, false),
+            AccountMeta::new(self.pool_sol_ata, false),
+        ]
+    }
+
+    /// routes from `user`'s ata of `source_mint` to its ata of `destination_mint`
+    pub fn swap_route_ix(
+        &self,
+        user: &Pubkey,
+        source_mint: &Pubkey,
+        destination_mint: &Pubkey,
+        amount_in: u64,
+        min_amount_out: u64,
+        hops: &[Vec<AccountMeta>],
+    ) -> Instruction {
+        let mut accounts = liquidity_pool::accounts::SwapRoute {
+            signer: *user,
+            user_source_ata: get_associated_token_address(user, source_mint),
+            user_destination_ata: get_associated_token_address(user, destination_mint),
+            token_program: spl_token::ID,
+            token_2022_program: token_2022::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(hops.iter().flatten().cloned());
+
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts,
+            data: liquidity_pool::instruction::SwapRoute { amount_in, min_amount_out, hops: hops.len() as u8 }.data(),
+        }
+    }
+
+    pub fn set_dynamic_fee_ix(&self, admin: &Pubkey, enabled: bool, min_fee_bps: u64, max_fee_bps: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::SetDynamicFee {
+                admin: *admin,
+                config: self.config,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::SetDynamicFee { enabled, min_fee_bps, max_fee_bps }.data(),
+        }
+    }
+
+    pub fn set_compound_reward_ix(&self, authority: &Pubkey, reward_bps: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::SetCompoundReward { authority: *authority, pool_pda: self.pool }.to_account_metas(None),
+            data: liquidity_pool::instruction::SetCompoundReward { reward_bps }.data(),
+        }
+    }
+
+    /// a side without a keeper account is compounded in full
+    pub fn compound_fees_ix(&self, keeper: &Pubkey, keeper_usdc_account: Option<Pubkey>, keeper_sol_account: Option<Pubkey>) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::CompoundFees {
+                keeper: *keeper,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                keeper_usdc_account,
+                keeper_sol_account,
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::CompoundFees {}.data(),
+        }
+    }
+
+    pub fn sync_ix(&self, authority: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::SyncReserves {
+                authority: *authority,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::Sync {}.data(),
+        }
+    }
+
+    /// the excess goes to `recipient`'s atas
+    pub fn skim_ix(&self, authority: &Pubkey, recipient: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::SkimReserves {
+                authority: *authority,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                recipient_usdc_ata: get_associated_token_address(recipient, &self.usdc_mint),
+                recipient_sol_ata: get_associated_token_address(recipient, &self.wrapped_sol_mint),
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::Skim {}.data(),
+        }
+    }
+
+    pub fn withdraw_ix(&self, user: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::WithDraw {
+                signer: *user,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                user_pda: self.user_pda(user),
+                user_usdc_ata: get_associated_token_address(user, &self.usdc_mint),
+                user_sol_ata: get_associated_token_address(user, &self.wrapped_sol_mint),
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_wrapped_sol_ata: self.pool_sol_ata,
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                reward_vault: None,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::Withdraw { native_sol: self.native_sol }.data(),
+        }
+    }
+
+    pub fn collect_fees_ix(&self, user: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::CollectFees {
+                signer: *user,
+                user_pda: self.user_pda(user),
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                user_usdc_ata: get_associated_token_address(user, &self.usdc_mint),
+                user_sol_ata: get_associated_token_address(user, &self.wrapped_sol_mint),
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::CollectFees {}.data(),
+        }
+    }
+
+    /// `position_mint` is a fresh keypair that has to sign too
+    pub fn open_position_ix(&self, user: &Pubkey, position_mint: &Pubkey, usdc_amount: u64, wrapped_sol_amount: u64) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::OpenPosition {
+                signer: *user,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                user_usdc_ata: get_associated_token_address(user, &self.usdc_mint),
+                user_sol_ata: get_associated_token_address(user, &self.wrapped_sol_mint),
+                pool_pda: self.pool,
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                position_mint: *position_mint,
+                position_pda: self.position_pda(position_mint),
+                position_token_account: get_associated_token_address(user, position_mint),
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                position_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                reward_vault: None,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::OpenPosition { usdc_amount, wrapped_sol_amount }.data(),
+        }
+    }
+
+    /// `holder` signs with its ata of the position mint
+    pub fn close_position_ix(&self, holder: &Pubkey, position_mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::ClosePosition {
+                signer: *holder,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                position_mint: *position_mint,
+                position_pda: self.position_pda(position_mint),
+                position_token_account: get_associated_token_address(holder, position_mint),
+                user_usdc_ata: get_associated_token_address(holder, &self.usdc_mint),
+                user_sol_ata: get_associated_token_address(holder, &self.wrapped_sol_mint),
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                position_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                reward_vault: None,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::ClosePosition {}.data(),
+        }
+    }
+
+    pub fn collect_position_fees_ix(&self, holder: &Pubkey, position_mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::CollectPositionFees {
+                signer: *holder,
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                position_mint: *position_mint,
+                position_pda: self.position_pda(position_mint),
+                position_token_account: get_associated_token_address(holder, position_mint),
+                pool_usdc_ata: self.pool_usdc_ata,
+                pool_sol_ata: self.pool_sol_ata,
+                user_usdc_ata: get_associated_token_address(holder, &self.usdc_mint),
+                user_sol_ata: get_associated_token_address(holder, &self.wrapped_sol_mint),
+                usdc_token_program: spl_token::ID,
+                sol_token_program: spl_token::ID,
+                position_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::CollectPositionFees {}.data(),
+        }
+    }
+
+    pub fn lock_ix(&self, user: &Pubkey, duration: i64) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::LockPosition {
+                signer: *user,
+                user_pda: self.user_pda(user),
+                usdc_mint: self.usdc_mint,
+                wrapped_sol_mint: self.wrapped_sol_mint,
+                pool_pda: self.pool,
+                reward_vault: None,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::LockPosition { duration }.data(),
+        }
+    }
+
+    pub fn expire_lock_ix(&self, owner: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::ExpireLock { user_pda: self.user_pda(owner), pool_pda: self.pool, reward_vault: None }
+                .to_account_metas(None),
+            data: liquidity_pool::instruction::ExpireLock {}.data(),
+        }
+    }
+
+    pub fn reward_vault(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"rewards", self.pool.as_ref()], &liquidity_pool::ID).0
+    }
+
+    /// `authority` funds the whole schedule from its ata of `reward_mint`
+    pub fn initialize_rewards_ix(&self, authority: &Pubkey, reward_mint: &Pubkey, emission_rate: u64, start_time: i64, end_time: i64) -> Instruction {
+        let reward_vault = self.reward_vault();
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::InitializeRewards {
+                authority: *authority,
+                pool_pda: self.pool,
+                reward_mint: *reward_mint,
+                reward_vault,
+                reward_vault_ata: get_associated_token_address(&reward_vault, reward_mint),
+                authority_reward_ata: get_associated_token_address(authority, reward_mint),
+                reward_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::InitializeRewards { emission_rate, start_time, end_time }.data(),
+        }
+    }
+
+    pub fn claim_rewards_ix(&self, user: &Pubkey, reward_mint: &Pubkey) -> Instruction {
+        let reward_vault = self.reward_vault();
+        Instruction {
+            program_id: liquidity_pool::ID,
+            accounts: liquidity_pool::accounts::ClaimRewards {
+                signer: *user,
+                user_pda: self.user_pda(user),
+                pool_pda: self.pool,
+                reward_vault,
+                reward_mint: *reward_mint,
+                reward_vault_ata: get_associated_token_address(&reward_vault, reward_mint),
+                user_reward_ata: get_associated_token_address(user, reward_mint),
+                reward_token_program: spl_token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: liquidity_pool::instruction::ClaimRewards {}.data(),
+        }
+    }
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[liquidity_pool::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// deploys the program under the upgradeable loader with `upgrade_authority`, the way
+/// `anchor deploy` does, so initialize_config can check it
+fn deploy_upgradeable(svm: &mut LiteSVM, program: &[u8], upgrade_authority: &Pubkey) {
+    let program_data = program_data_address();
+
+    // bincode of UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address }
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(upgrade_authority.as_ref());
+    data.extend_from_slice(program);
+    let lamports = svm.minimum_balance_for_rent_exemption(data.len());
+    let account = Account { lamports, data, owner: bpf_loader_upgradeable::ID, executable: false, rent_epoch: 0 };
+    svm.set_account(program_data, account).unwrap();
+
+    // UpgradeableLoaderState::Program { programdata_address }, loaded from the account above
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(program_data.as_ref());
+    let lamports = svm.minimum_balance_for_rent_exemption(data.len());
+    let account = Account { lamports, data, owner: bpf_loader_upgradeable::ID, executable: true, rent_epoch: 0 };
+    svm.set_account(liquidity_pool::ID, account).unwrap();
+}
+
+/// fails unless the transaction's only instruction failed with the program's `error`
+pub fn assert_program_error(result: TransactionResult, error: impl Into<u32>) {
+    let failed = result.expect_err("transaction should have failed");
+    let expected = TransactionError::InstructionError(0, InstructionError::Custom(error.into()));
+    assert_eq!(failed.err, expected, "{}", failed.meta.pretty_logs());
+}
+
+fn create_mint(svm: &mut LiteSVM, payer: &Keypair, decimals: u8) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = svm.minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN);
+
+    let create_account = system_instruction::create_account(
+        &payer.pubkey(),
+        &mint.pubkey(),
+        rent,
+        spl_token::state::Mint::LEN as u64,
+        &spl_token::ID,
+    );
+    let initialize_mint = spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer.pubkey(), None, decimals).unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[create_account, initialize_mint],
+        Some(&payer.pubkey()),
+        &[payer, &mint],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).unwrap();
+
+    mint.pubkey()
+}
@@ -0,0 +1,113 @@
+//! fee compounding against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::{error::PoolError, state::MAX_COMPOUND_REWARD_BPS};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+const SOL_FEES: u64 = LAMPORTS_PER_SOL / 100;
+const REWARD_BPS: u64 = 100;
+
+/// pool with fees in both buckets, `lp` is its authority and set the keeper reward
+fn pool_with_fees() -> (TestPool, Keypair) {
+    let mut pool = TestPool::new();
+    let lp = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let trader = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+    let ix = pool.swap_ix(&trader.pubkey(), 100 * USDC, true);
+    pool.send(ix, &[&trader]).unwrap();
+
+    // swaps charge their fee in usdc, so the sol bucket is filled by hand
+    let mut state = pool.pool_state().unwrap();
+    state.add_fees(0, SOL_FEES).unwrap();
+    pool.set_pool_state(&state);
+    let (pool_key, wrapped_sol_mint) = (pool.pool, pool.wrapped_sol_mint);
+    pool.fund(&pool_key, &wrapped_sol_mint, SOL_FEES);
+
+    let ix = pool.set_compound_reward_ix(&lp.pubkey(), REWARD_BPS);
+    pool.send(ix, &[&lp]).unwrap();
+
+    (pool, lp)
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn only_the_authority_sets_a_bounded_keeper_reward() {
+    let (mut pool, lp) = pool_with_fees();
+    let stranger = pool.new_user(0, 0);
+
+    let ix = pool.set_compound_reward_ix(&stranger.pubkey(), REWARD_BPS);
+    assert_program_error(pool.send(ix, &[&stranger]), PoolError::Unauthorized);
+    let ix = pool.set_compound_reward_ix(&lp.pubkey(), MAX_COMPOUND_REWARD_BPS + 1);
+    assert_program_error(pool.send(ix, &[&lp]), PoolError::InvalidFeeBounds);
+
+    assert_eq!(pool.pool_state().unwrap().compound_reward_bps, REWARD_BPS);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn keeper_is_paid_its_bps_of_each_side() {
+    let (mut pool, _) = pool_with_fees();
+    let keeper = pool.new_user(0, 0);
+    let keeper_usdc = get_associated_token_address(&keeper.pubkey(), &pool.usdc_mint);
+    let keeper_sol = get_associated_token_address(&keeper.pubkey(), &pool.wrapped_sol_mint);
+
+    let mut expected = pool.pool_state().unwrap();
+    let (_, (reward_usdc, reward_sol)) = expected.compound_fees(REWARD_BPS, REWARD_BPS).unwrap();
+    assert!(reward_usdc > 0 && reward_sol > 0);
+
+    let ix = pool.compound_fees_ix(&keeper.pubkey(), Some(keeper_usdc), Some(keeper_sol));
+    pool.send(ix, &[&keeper]).unwrap();
+
+    assert_eq!(pool.token_balance(&keeper_usdc), reward_usdc);
+    assert_eq!(pool.token_balance(&keeper_sol), reward_sol);
+    let state = pool.pool_state().unwrap();
+    assert_eq!((state.total_usdc_deposit, state.total_sol_deposit), (expected.total_usdc_deposit, expected.total_sol_deposit));
+    assert_eq!((state.fees_collected_usdc, state.fees_collected_sol), (0, 0));
+
+    // everything earned so far is in the reserves
+    let ix = pool.compound_fees_ix(&keeper.pubkey(), Some(keeper_usdc), Some(keeper_sol));
+    assert_program_error(pool.send(ix, &[&keeper]), PoolError::NoFeesToCompound);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn side_without_a_keeper_account_is_compounded_in_full() {
+    let (mut pool, _) = pool_with_fees();
+    let keeper = pool.new_user(0, 0);
+    let keeper_usdc = get_associated_token_address(&keeper.pubkey(), &pool.usdc_mint);
+    let keeper_sol = get_associated_token_address(&keeper.pubkey(), &pool.wrapped_sol_mint);
+
+    let mut expected = pool.pool_state().unwrap();
+    let (_, (reward_usdc, _)) = expected.compound_fees(REWARD_BPS, 0).unwrap();
+
+    let ix = pool.compound_fees_ix(&keeper.pubkey(), Some(keeper_usdc), None);
+    pool.send(ix, &[&keeper]).unwrap();
+
+    assert_eq!(pool.token_balance(&keeper_usdc), reward_usdc);
+    assert_eq!(pool.token_balance(&keeper_sol), 0);
+    let state = pool.pool_state().unwrap();
+    assert_eq!((state.total_usdc_deposit, state.total_sol_deposit), (expected.total_usdc_deposit, expected.total_sol_deposit));
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn compound_is_rejected_during_a_flash_loan() {
+    let (mut pool, _) = pool_with_fees();
+    let keeper = pool.new_user(0, 0);
+
+    // what flash_borrow leaves behind until flash_repay runs later in the transaction
+    let mut state = pool.pool_state().unwrap();
+    state.flash_loan_active = 1;
+    pool.set_pool_state(&state);
+
+    let ix = pool.compound_fees_ix(&keeper.pubkey(), None, None);
+    assert_program_error(pool.send(ix, &[&keeper]), PoolError::FlashLoanActive);
+    assert_eq!(pool.pool_state().unwrap().fees_collected_sol, SOL_FEES);
+}
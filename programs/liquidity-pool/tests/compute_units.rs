@@ -0,0 +1,85 @@
+//! compute unit ceilings for the hot instructions. a change that pushes one of them
+//! over its ceiling fails here instead of on mainnet. ignored by a plain `cargo test`,
+//! `anchor run test-sbf` rebuilds the program and runs them.
+
+mod common;
+
+use common::{TestPool, LAMPORTS_PER_SOL};
+use litesvm::types::TransactionMetadata;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+// each test prints what it used. the ceilings below have not been measured yet, no sbf build
+// has run them. set each to ~20% over what `anchor run test-sbf` prints, with the fuzz target
+// passing on the same build, and replace this note with the numbers.
+// first deposit pays for creating the pool, the user position and both vaults
+const DEPOSIT_INIT_CU_LIMIT: u64 = 150_000;
+const DEPOSIT_CU_LIMIT: u64 = 60_000;
+const SWAP_CU_LIMIT: u64 = 60_000;
+const WITHDRAW_CU_LIMIT: u64 = 60_000;
+
+fn assert_within(name: &str, meta: &TransactionMetadata, limit: u64) {
+    println!("{name}: {} CU (limit {limit})", meta.compute_units_consumed);
+    assert!(
+        meta.compute_units_consumed <= limit,
+        "{name} used {} CU, over the {limit} CU ceiling\n{}",
+        meta.compute_units_consumed,
+        meta.pretty_logs()
+    );
+}
+
+/// pool seeded with 5m usdc / 100k sol
+fn seeded_pool() -> TestPool {
+    let mut pool = TestPool::new();
+
+    let lp = pool.new_user(10_000_000 * USDC, 200_000 * LAMPORTS_PER_SOL);
+    let ix = pool.deposit_ix(&lp.pubkey(), 5_000_000 * USDC, 100_000 * LAMPORTS_PER_SOL);
+    let meta = pool.send(ix, &[&lp]).unwrap();
+    assert_within("deposit (init)", &meta, DEPOSIT_INIT_CU_LIMIT);
+
+    pool
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn deposit_stays_within_budget() {
+    let mut pool = seeded_pool();
+
+    let user = pool.new_user(1_000 * USDC, 100 * LAMPORTS_PER_SOL);
+    let ix = pool.deposit_ix(&user.pubkey(), 50 * USDC, LAMPORTS_PER_SOL);
+    let meta = pool.send(ix, &[&user]).unwrap();
+
+    assert_within("deposit", &meta, DEPOSIT_CU_LIMIT);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn swaps_stay_within_budget() {
+    let mut pool = seeded_pool();
+
+    let trader = pool.new_user(10_000 * USDC, 100 * LAMPORTS_PER_SOL);
+
+    let ix = pool.swap_ix(&trader.pubkey(), 500 * USDC, true);
+    let meta = pool.send(ix, &[&trader]).unwrap();
+    assert_within("swap usdc -> sol", &meta, SWAP_CU_LIMIT);
+
+    let ix = pool.swap_ix(&trader.pubkey(), LAMPORTS_PER_SOL, false);
+    let meta = pool.send(ix, &[&trader]).unwrap();
+    assert_within("swap sol -> usdc", &meta, SWAP_CU_LIMIT);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn withdraw_stays_within_budget() {
+    let mut pool = seeded_pool();
+
+    let user = pool.new_user(1_000 * USDC, 100 * LAMPORTS_PER_SOL);
+    let ix = pool.deposit_ix(&user.pubkey(), 50 * USDC, LAMPORTS_PER_SOL);
+    pool.send(ix, &[&user]).unwrap();
+
+    let ix = pool.withdraw_ix(&user.pubkey());
+    let meta = pool.send(ix, &[&user]).unwrap();
+
+    assert_within("withdraw", &meta, WITHDRAW_CU_LIMIT);
+}
@@ -0,0 +1,24 @@
+//! global config against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::error::PoolError;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn only_the_upgrade_authority_creates_the_config() {
+    let mut pool = TestPool::without_config();
+    let stranger = Keypair::new();
+    pool.svm.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let ix = pool.initialize_config_ix(&stranger.pubkey());
+    assert_program_error(pool.send(ix, &[&stranger]), PoolError::NotUpgradeAuthority);
+    assert!(pool.svm.get_account(&pool.config).is_none());
+
+    let ix = pool.initialize_config_ix(&pool.payer.pubkey());
+    pool.send(ix, &[]).unwrap();
+    assert!(pool.svm.get_account(&pool.config).is_some());
+}
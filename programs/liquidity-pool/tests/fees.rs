@@ -0,0 +1,42 @@
+//! fee collection against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_lang::error::ErrorCode;
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn positions_only_collect_fees_from_their_own_pool() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let bob = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let trader = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&alice.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&alice]).unwrap();
+
+    pool.use_fee_tier(5);
+    let ix = pool.deposit_ix(&bob.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&bob]).unwrap();
+    let ix = pool.swap_ix(&trader.pubkey(), 100 * USDC, true);
+    pool.send(ix, &[&trader]).unwrap();
+    let fees = pool.pool_state().unwrap().fees_collected_usdc;
+    assert!(fees > 0);
+
+    // alice's position is in the first pool, there is none here to collect for
+    let ix = pool.collect_fees_ix(&alice.pubkey());
+    assert_program_error(pool.send(ix, &[&alice]), ErrorCode::AccountNotInitialized);
+    assert_eq!(pool.pool_state().unwrap().fees_collected_usdc, fees);
+
+    // bob holds every share, so all of it is paid out less the accumulator's rounding
+    let bob_usdc = get_associated_token_address(&bob.pubkey(), &pool.usdc_mint);
+    let before = pool.token_balance(&bob_usdc);
+    let ix = pool.collect_fees_ix(&bob.pubkey());
+    pool.send(ix, &[&bob]).unwrap();
+    assert!(fees - (pool.token_balance(&bob_usdc) - before) <= 1);
+}
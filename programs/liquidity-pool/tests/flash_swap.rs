@@ -0,0 +1,126 @@
+//! flash_swap against the built program with the flash-swap-callback program as the
+//! borrower, `anchor run test-sbf` builds both and runs them
+
+mod common;
+
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::{error::PoolError, state::{Pool, MAX_FEE_BPS}};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const CALLBACK_SO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/flash_swap_callback.so");
+const USDC: u64 = 1_000_000;
+
+fn pool_with_callback() -> (TestPool, Keypair) {
+    let mut pool = TestPool::new();
+    let program = std::fs::read(CALLBACK_SO).unwrap_or_else(|err| panic!("{CALLBACK_SO}: {err}, run `anchor build` first"));
+    pool.svm.add_program(flash_swap_callback::ID, &program);
+
+    let lp = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+
+    let trader = pool.new_user(1_000 * USDC, 0);
+    (pool, trader)
+}
+
+/// borrows `sol_out` and has the callback pay `repay_usdc` into the usdc vault
+fn flash_swap_ix(pool: &TestPool, trader: &Pubkey, sol_out: u64, repay_usdc: u64) -> Instruction {
+    let trader_usdc = get_associated_token_address(trader, &pool.usdc_mint);
+
+    let mut accounts = liquidity_pool::accounts::FlashSwap {
+        signer: *trader,
+        usdc_mint: pool.usdc_mint,
+        wrapped_sol_mint: pool.wrapped_sol_mint,
+        user_usdc_ata: trader_usdc,
+        user_sol_ata: get_associated_token_address(trader, &pool.wrapped_sol_mint),
+        pool_pda: pool.pool,
+        pool_usdc_ata: pool.pool_usdc_ata,
+        pool_sol_ata: pool.pool_sol_ata,
+        callback_program: flash_swap_callback::ID,
+        usdc_token_program: spl_token::ID,
+        sol_token_program: spl_token::ID,
+    }
+    .to_account_metas(None);
+    // the remaining accounts are handed to the callback as its own
+    accounts.extend(
+        flash_swap_callback::accounts::Repay {
+            payer: *trader,
+            mint: pool.usdc_mint,
+            from: trader_usdc,
+            to: pool.pool_usdc_ata,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+    );
+
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts,
+        data: liquidity_pool::instruction::FlashSwap {
+            usdc_out: 0,
+            wrapped_sol_out: sol_out,
+            callback_data: flash_swap_callback::instruction::Repay { amount: repay_usdc }.data(),
+        }
+        .data(),
+    }
+}
+
+/// usdc that has to stay in the pool, after the fee, to keep k when `sol_out` leaves
+fn usdc_owed(state: &Pool, sol_out: u64) -> u64 {
+    let (usdc, sol) = (state.total_usdc_deposit as u128, state.total_sol_deposit as u128);
+    ((usdc * sol).div_ceil(sol - sol_out as u128) - usdc) as u64
+}
+
+/// grosses `net` up so what's left after flash_swap's rounded up fee still covers it
+fn with_fee(net: u64, fee_bps: u64) -> u64 {
+    ((net as u128 * MAX_FEE_BPS as u128).div_ceil((MAX_FEE_BPS - fee_bps) as u128) + 1) as u64
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn callback_that_repays_keeps_the_sol() {
+    let (mut pool, trader) = pool_with_callback();
+    let sol_out = LAMPORTS_PER_SOL / 10;
+
+    let mut before = pool.pool_state().unwrap();
+    let fee_bps = before.swap_fee_bps(pool.now()).unwrap();
+    let repay = with_fee(usdc_owed(&before, sol_out), fee_bps);
+
+    let ix = flash_swap_ix(&pool, &trader.pubkey(), sol_out, repay);
+    pool.send(ix, &[&trader]).unwrap();
+
+    let trader_usdc = get_associated_token_address(&trader.pubkey(), &pool.usdc_mint);
+    let trader_sol = get_associated_token_address(&trader.pubkey(), &pool.wrapped_sol_mint);
+    assert_eq!(pool.token_balance(&trader_sol), sol_out);
+    assert_eq!(pool.token_balance(&trader_usdc), 1_000 * USDC - repay);
+
+    let fee = (repay as u128 * fee_bps as u128).div_ceil(MAX_FEE_BPS as u128) as u64;
+    let after = pool.pool_state().unwrap();
+    assert_eq!(after.flash_loan_active, 0);
+    assert_eq!(after.fees_collected_usdc, before.fees_collected_usdc + fee);
+    assert_eq!(after.total_usdc_deposit, before.total_usdc_deposit + repay - fee);
+    assert_eq!(after.total_sol_deposit, before.total_sol_deposit - sol_out);
+    assert!(after.total_usdc_deposit as u128 * after.total_sol_deposit as u128 >= before.total_usdc_deposit as u128 * before.total_sol_deposit as u128);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn callback_that_underpays_fails_the_k_check() {
+    let (mut pool, trader) = pool_with_callback();
+    let sol_out = LAMPORTS_PER_SOL / 10;
+
+    // enough to keep k before the fee, but not after it
+    let before = pool.pool_state().unwrap();
+    let ix = flash_swap_ix(&pool, &trader.pubkey(), sol_out, usdc_owed(&before, sol_out));
+    assert_program_error(pool.send(ix, &[&trader]), PoolError::InvariantViolated);
+
+    // the whole transaction rolled back, the loan included
+    let after = pool.pool_state().unwrap();
+    assert_eq!(after.flash_loan_active, 0);
+    assert_eq!((after.total_usdc_deposit, after.total_sol_deposit), (before.total_usdc_deposit, before.total_sol_deposit));
+    let trader_usdc = get_associated_token_address(&trader.pubkey(), &pool.usdc_mint);
+    assert_eq!(pool.token_balance(&trader_usdc), 1_000 * USDC);
+}
@@ -0,0 +1,86 @@
+//! locked positions against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{assert_program_error, TestPool, FEE_TIER, LAMPORTS_PER_SOL};
+use liquidity_pool::{error::PoolError, state::MIN_LOCK_SECS};
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn locked_positions_cant_withdraw_until_the_lock_ends() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&alice.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&alice]).unwrap();
+    let ix = pool.lock_ix(&alice.pubkey(), MIN_LOCK_SECS);
+    pool.send(ix, &[&alice]).unwrap();
+
+    let user = pool.user_state(&alice.pubkey()).unwrap();
+    assert_eq!(pool.pool_state().unwrap().boosted_shares, user.boost_shares);
+    assert!(user.boost_shares > 0);
+
+    let ix = pool.withdraw_ix(&alice.pubkey());
+    assert_program_error(pool.send(ix, &[&alice]), PoolError::PositionLocked);
+
+    pool.warp(MIN_LOCK_SECS);
+    let ix = pool.withdraw_ix(&alice.pubkey());
+    pool.send(ix, &[&alice]).unwrap();
+    assert!(pool.user_state(&alice.pubkey()).is_none());
+    assert_eq!(pool.pool_state().unwrap().boosted_shares, 0);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn locks_only_boost_the_positions_own_pool() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let bob = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&alice.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&alice]).unwrap();
+
+    pool.use_fee_tier(5);
+    let ix = pool.deposit_ix(&bob.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&bob]).unwrap();
+
+    // alice has no position in the second pool to lock
+    let ix = pool.lock_ix(&alice.pubkey(), MIN_LOCK_SECS);
+    assert_program_error(pool.send(ix, &[&alice]), ErrorCode::AccountNotInitialized);
+    assert_eq!(pool.pool_state().unwrap().boosted_shares, 0);
+
+    pool.use_fee_tier(FEE_TIER);
+    let ix = pool.lock_ix(&alice.pubkey(), MIN_LOCK_SECS);
+    pool.send(ix, &[&alice]).unwrap();
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn anyone_can_expire_an_ended_lock() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let keeper = pool.new_user(0, 0);
+
+    let ix = pool.deposit_ix(&alice.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&alice]).unwrap();
+    let ix = pool.lock_ix(&alice.pubkey(), MIN_LOCK_SECS);
+    pool.send(ix, &[&alice]).unwrap();
+
+    let ix = pool.expire_lock_ix(&alice.pubkey());
+    assert_program_error(pool.send(ix, &[&keeper]), PoolError::LockNotExpired);
+
+    // the keeper only pays the fee, alice doesn't sign
+    pool.warp(MIN_LOCK_SECS);
+    let ix = pool.expire_lock_ix(&alice.pubkey());
+    pool.send(ix, &[&keeper]).unwrap();
+    let user = pool.user_state(&alice.pubkey()).unwrap();
+    assert_eq!((user.boost_shares, user.lock_boost_bps), (0, 0));
+    assert_eq!(pool.pool_state().unwrap().boosted_shares, 0);
+
+    let ix = pool.expire_lock_ix(&alice.pubkey());
+    assert_program_error(pool.send(ix, &[&keeper]), PoolError::LockNotExpired);
+}
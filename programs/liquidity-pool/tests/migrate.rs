@@ -0,0 +1,163 @@
+//! migrating pools and positions from the baseline layout, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program},
+    Discriminator, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address},
+    token::spl_token,
+};
+use common::{assert_program_error, TestPool, FEE_TIER, LAMPORTS_PER_SOL};
+use liquidity_pool::{
+    error::PoolError,
+    state::{Pool, User, POOL_VERSION, USER_VERSION},
+};
+use solana_account::Account;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+/// writes a program owned account with the given discriminator and borsh fields
+fn set_program_account(pool: &mut TestPool, address: Pubkey, discriminator: &[u8], fields: &[u8]) {
+    let data = [discriminator, fields].concat();
+    let lamports = pool.svm.minimum_balance_for_rent_exemption(data.len());
+    let account = Account { lamports, data, owner: liquidity_pool::ID, executable: false, rent_epoch: 0 };
+    pool.svm.set_account(address, account).unwrap();
+}
+
+/// where baseline positions live, one per owner for every pool
+fn legacy_user_pda(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"lp", owner.as_ref()], &liquidity_pool::ID).0
+}
+
+/// baseline position at [b"lp", owner] holding `shares`
+fn set_legacy_user(pool: &mut TestPool, owner: &Pubkey, shares: u64) -> Pubkey {
+    let mut fields = owner.to_bytes().to_vec();
+    for value in [500 * USDC, 5 * LAMPORTS_PER_SOL, shares] {
+        fields.extend_from_slice(&value.to_le_bytes());
+    }
+    let address = legacy_user_pda(owner);
+    set_program_account(pool, address, User::DISCRIMINATOR, &fields);
+    address
+}
+
+fn migrate_position_ix(pool: &TestPool, admin: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::MigratePosition {
+            admin: *admin,
+            config: pool.config,
+            pool_pda: pool.pool,
+            owner: *owner,
+            legacy_user: legacy_user_pda(owner),
+            user_pda: pool.user_pda(owner),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::MigratePosition {}.data(),
+    }
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn baseline_pools_keep_working_after_migration() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let trader = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let (usdc_mint, wrapped_sol_mint) = (pool.usdc_mint, pool.wrapped_sol_mint);
+
+    // a baseline pool at [b"pool", usdc, sol] holding 500 usdc / 5 sol and 3 usdc of fees,
+    // every share belongs to alice
+    let (legacy_pool, legacy_bump) =
+        Pubkey::find_program_address(&[b"pool", usdc_mint.as_ref(), wrapped_sol_mint.as_ref()], &liquidity_pool::ID);
+    let shares: u64 = 1_000_000;
+    let mut fields = Vec::new();
+    for value in [500 * USDC, 5 * LAMPORTS_PER_SOL, 3 * USDC, 30, shares] {
+        fields.extend_from_slice(&value.to_le_bytes());
+    }
+    fields.extend_from_slice(&[legacy_bump, 1]);
+    set_program_account(&mut pool, legacy_pool, Pool::DISCRIMINATOR, &fields);
+    let legacy_usdc_ata = pool.fund(&legacy_pool, &usdc_mint, 503 * USDC);
+    let legacy_sol_ata = pool.fund(&legacy_pool, &wrapped_sol_mint, 5 * LAMPORTS_PER_SOL);
+
+    let alice_lp = set_legacy_user(&mut pool, &alice.pubkey(), shares);
+
+    // the payer is the config admin and becomes the pool's authority
+    let admin = pool.payer.pubkey();
+    let migrate_pool = Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: liquidity_pool::accounts::MigrateLegacyPool {
+            admin,
+            config: pool.config,
+            usdc_mint,
+            wrapped_sol_mint,
+            legacy_pool,
+            legacy_usdc_ata,
+            legacy_sol_ata,
+            pool_pda: pool.pool,
+            pool_usdc_ata: pool.pool_usdc_ata,
+            pool_sol_ata: pool.pool_sol_ata,
+            usdc_token_program: spl_token::ID,
+            sol_token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: liquidity_pool::instruction::MigrateLegacyPool { fee_tier: FEE_TIER }.data(),
+    };
+    pool.send(migrate_pool, &[]).unwrap();
+
+    assert!(pool.svm.get_account(&legacy_pool).is_none_or(|account| account.lamports == 0));
+    let state = pool.pool_state().unwrap();
+    assert_eq!((state.total_usdc_deposit, state.total_sol_deposit, state.fees_collected_usdc), (500 * USDC, 5 * LAMPORTS_PER_SOL, 3 * USDC));
+    assert_eq!((state.fee_tier, state.authority, state.version), (FEE_TIER, admin, POOL_VERSION));
+    assert_eq!(pool.token_balance(&pool.pool_usdc_ata), 503 * USDC);
+
+    let migrate_position = migrate_position_ix(&pool, &admin, &alice.pubkey());
+    let alice_rent = pool.lamports(&alice_lp);
+    let before = pool.lamports(&alice.pubkey());
+    pool.send(migrate_position, &[]).unwrap();
+    assert_eq!(pool.lamports(&alice_lp), 0);
+    assert_eq!(pool.lamports(&alice.pubkey()) - before, alice_rent);
+    assert_eq!(pool.pool_state().unwrap().legacy_shares, 0);
+    let position = pool.user_state(&alice.pubkey()).unwrap();
+    assert_eq!((position.pool, position.version, position.total_shares), (pool.pool, USER_VERSION, shares));
+
+    let ix = pool.deposit_ix(&alice.pubkey(), 50 * USDC, LAMPORTS_PER_SOL / 2);
+    pool.send(ix, &[&alice]).unwrap();
+    let ix = pool.swap_ix(&trader.pubkey(), 10 * USDC, true);
+    pool.send(ix, &[&trader]).unwrap();
+
+    // alice holds every share, so the withdrawal returns the reserves and the baseline fees
+    let alice_usdc = get_associated_token_address(&alice.pubkey(), &usdc_mint);
+    let before = pool.token_balance(&alice_usdc);
+    let ix = pool.withdraw_ix(&alice.pubkey());
+    pool.send(ix, &[&alice]).unwrap();
+    assert!(pool.token_balance(&alice_usdc) - before > 553 * USDC);
+    assert!(pool.user_state(&alice.pubkey()).is_none());
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn baseline_positions_only_migrate_into_pools_that_owe_them() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let mallory = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    // mallory opens a pool of their own, it owes no baseline shares
+    let ix = pool.deposit_ix(&mallory.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&mallory]).unwrap();
+    let alice_lp = set_legacy_user(&mut pool, &alice.pubkey(), 1_000_000);
+
+    let ix = migrate_position_ix(&pool, &mallory.pubkey(), &alice.pubkey());
+    assert_program_error(pool.send(ix, &[&mallory]), PoolError::Unauthorized);
+
+    let ix = migrate_position_ix(&pool, &pool.payer.pubkey(), &alice.pubkey());
+    assert_program_error(pool.send(ix, &[]), PoolError::LegacySharesExceeded);
+    assert!(pool.user_state(&alice.pubkey()).is_none());
+    assert!(pool.lamports(&alice_lp) > 0);
+}
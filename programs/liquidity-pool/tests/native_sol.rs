@@ -0,0 +1,117 @@
+//! deposit, swap and withdraw with `native_sol` set against the built program,
+//! `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::error::PoolError;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+/// native sol pool seeded by `lp`
+fn seeded_pool() -> (TestPool, Keypair) {
+    let mut pool = TestPool::with_native_sol();
+    let lp = pool.new_user(1_000 * USDC, 0);
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+    (pool, lp)
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn deposit_wraps_the_signers_lamports() {
+    let mut pool = TestPool::with_native_sol();
+    let lp = pool.new_user(1_000 * USDC, 0);
+    let before = pool.lamports(&lp.pubkey());
+
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+
+    // the wsol account is closed again, only the position's rent stays spent
+    let lp_wsol = get_associated_token_address(&lp.pubkey(), &spl_token::native_mint::ID);
+    assert_eq!(pool.lamports(&lp_wsol), 0);
+    let position_rent = pool.lamports(&pool.user_pda(&lp.pubkey()));
+    assert_eq!(before - pool.lamports(&lp.pubkey()), 5 * LAMPORTS_PER_SOL + position_rent);
+    assert_eq!(pool.token_balance(&pool.pool_sol_ata), 5 * LAMPORTS_PER_SOL);
+    assert_eq!(pool.pool_state().unwrap().total_sol_deposit, 5 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn swap_pays_sol_out_as_lamports() {
+    let (mut pool, _) = seeded_pool();
+    let trader = pool.new_user(1_000 * USDC, 0);
+    let before = pool.lamports(&trader.pubkey());
+
+    let mut state = pool.pool_state().unwrap();
+    let fee_bps = state.swap_fee_bps(pool.now()).unwrap();
+    let (sol_out, _) = state.swap_usdc_for_sol(100 * USDC, fee_bps).unwrap();
+
+    let ix = pool.swap_ix(&trader.pubkey(), 100 * USDC, true);
+    pool.send(ix, &[&trader]).unwrap();
+
+    // the wsol account the swap opened is closed with its rent refunded
+    let trader_wsol = get_associated_token_address(&trader.pubkey(), &spl_token::native_mint::ID);
+    assert_eq!(pool.lamports(&trader_wsol), 0);
+    assert_eq!(pool.lamports(&trader.pubkey()) - before, sol_out);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn swap_takes_sol_in_as_lamports() {
+    let (mut pool, _) = seeded_pool();
+    let trader = pool.new_user(0, 0);
+    // sol in goes through an existing wsol account, the payer opened this one
+    let trader_wsol = pool.fund(&trader.pubkey(), &spl_token::native_mint::ID, 0);
+    let wsol_rent = pool.lamports(&trader_wsol);
+    let before = pool.lamports(&trader.pubkey());
+
+    let mut state = pool.pool_state().unwrap();
+    let fee_bps = state.swap_fee_bps(pool.now()).unwrap();
+    let (usdc_out, _) = state.swap_sol_for_usdc(LAMPORTS_PER_SOL, fee_bps).unwrap();
+
+    let ix = pool.swap_ix(&trader.pubkey(), LAMPORTS_PER_SOL, false);
+    pool.send(ix, &[&trader]).unwrap();
+
+    assert_eq!(pool.lamports(&trader_wsol), 0);
+    assert_eq!(pool.lamports(&trader.pubkey()), before - LAMPORTS_PER_SOL + wsol_rent);
+    let trader_usdc = get_associated_token_address(&trader.pubkey(), &pool.usdc_mint);
+    assert_eq!(pool.token_balance(&trader_usdc), usdc_out);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn withdraw_pays_sol_as_lamports() {
+    let (mut pool, _) = seeded_pool();
+    let user = pool.new_user(100 * USDC, 0);
+    let ix = pool.deposit_ix(&user.pubkey(), 100 * USDC, LAMPORTS_PER_SOL);
+    pool.send(ix, &[&user]).unwrap();
+
+    let (mut state, mut position) = (pool.pool_state().unwrap(), pool.user_state(&user.pubkey()).unwrap());
+    let (_, sol_fees) = position.collect_fees(&mut state).unwrap();
+    let (_, sol_out) = state.remove_liquidity(position.total_shares).unwrap();
+    let position_rent = pool.lamports(&pool.user_pda(&user.pubkey()));
+    let before = pool.lamports(&user.pubkey());
+
+    let ix = pool.withdraw_ix(&user.pubkey());
+    pool.send(ix, &[&user]).unwrap();
+
+    // the position's rent comes back with the sol, the wsol account opened for it is closed
+    let user_wsol = get_associated_token_address(&user.pubkey(), &spl_token::native_mint::ID);
+    assert_eq!(pool.lamports(&user_wsol), 0);
+    assert_eq!(pool.lamports(&user.pubkey()) - before, sol_out + sol_fees + position_rent);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn native_sol_needs_the_native_mint() {
+    let mut pool = TestPool::new();
+    let user = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    pool.native_sol = true;
+
+    let ix = pool.deposit_ix(&user.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    assert_program_error(pool.send(ix, &[&user]), PoolError::NotNativeMint);
+}
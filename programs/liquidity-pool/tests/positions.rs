@@ -0,0 +1,99 @@
+//! nft positions against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_lang::{prelude::Pubkey, solana_program::program_option::COption};
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::error::PoolError;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+/// pool seeded by its own lp, with `holder` owning a second position as an nft
+fn pool_with_position(holder: &Keypair) -> (TestPool, Pubkey) {
+    let mut pool = TestPool::new();
+    let lp = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let (usdc_mint, wrapped_sol_mint) = (pool.usdc_mint, pool.wrapped_sol_mint);
+    pool.svm.airdrop(&holder.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    pool.fund(&holder.pubkey(), &usdc_mint, 1_000 * USDC);
+    pool.fund(&holder.pubkey(), &wrapped_sol_mint, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+
+    let position_mint = Keypair::new();
+    let ix = pool.open_position_ix(&holder.pubkey(), &position_mint.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[holder, &position_mint]).unwrap();
+
+    (pool, position_mint.pubkey())
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn position_mint_supply_stays_at_one() {
+    let alice = Keypair::new();
+    let (mut pool, position_mint) = pool_with_position(&alice);
+
+    let mint = pool.mint_state(&position_mint);
+    assert_eq!(mint.supply, 1);
+    assert_eq!(mint.decimals, 0);
+    assert_eq!(mint.mint_authority, COption::None);
+
+    // the opener was the mint authority until the program revoked it
+    let alice_ata = get_associated_token_address(&alice.pubkey(), &position_mint);
+    let mint_to = spl_token::instruction::mint_to(&spl_token::ID, &position_mint, &alice_ata, &alice.pubkey(), &[], 1).unwrap();
+    assert!(pool.send(mint_to, &[&alice]).is_err());
+    assert_eq!(pool.mint_state(&position_mint).supply, 1);
+    assert_eq!(pool.token_balance(&alice_ata), 1);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn transferred_position_belongs_to_the_new_holder() {
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let (mut pool, position_mint) = pool_with_position(&alice);
+    pool.svm.airdrop(&bob.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+
+    let trader = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let ix = pool.swap_ix(&trader.pubkey(), 100 * USDC, true);
+    pool.send(ix, &[&trader]).unwrap();
+    let ix = pool.swap_ix(&trader.pubkey(), LAMPORTS_PER_SOL, false);
+    pool.send(ix, &[&trader]).unwrap();
+
+    let before_transfer = pool.position_state(&position_mint).unwrap();
+    pool.transfer_position(&alice, &bob.pubkey(), &position_mint);
+
+    // the position account is untouched, its checkpoints and shares move with the token
+    let position = pool.position_state(&position_mint).unwrap();
+    assert_eq!(position.owner, position_mint);
+    assert_eq!(position.total_shares, before_transfer.total_shares);
+    assert_eq!(position.fee_growth_inside_last_usdc, before_transfer.fee_growth_inside_last_usdc);
+    assert_eq!(position.fee_growth_inside_last_sol, before_transfer.fee_growth_inside_last_sol);
+
+    let ix = pool.collect_position_fees_ix(&alice.pubkey(), &position_mint);
+    assert_program_error(pool.send(ix, &[&alice]), PoolError::NotPositionHolder);
+    let ix = pool.close_position_ix(&alice.pubkey(), &position_mint);
+    assert_program_error(pool.send(ix, &[&alice]), PoolError::NotPositionHolder);
+
+    // bob is paid everything the position earned while alice held it
+    let (mut pool_copy, mut position_copy) = (pool.pool_state().unwrap(), position);
+    let (usdc_fees, sol_fees) = position_copy.collect_fees(&mut pool_copy).unwrap();
+    assert!(usdc_fees > 0);
+
+    let ix = pool.collect_position_fees_ix(&bob.pubkey(), &position_mint);
+    pool.send(ix, &[&bob]).unwrap();
+    let bob_usdc = get_associated_token_address(&bob.pubkey(), &pool.usdc_mint);
+    let bob_sol = get_associated_token_address(&bob.pubkey(), &pool.wrapped_sol_mint);
+    assert_eq!(pool.token_balance(&bob_usdc), usdc_fees);
+    assert_eq!(pool.token_balance(&bob_sol), sol_fees);
+
+    let ix = pool.close_position_ix(&bob.pubkey(), &position_mint);
+    pool.send(ix, &[&bob]).unwrap();
+    assert!(pool.position_state(&position_mint).is_none());
+    assert_eq!(pool.mint_state(&position_mint).supply, 0);
+    assert!(pool.token_balance(&bob_usdc) > usdc_fees);
+    assert!(pool.token_balance(&bob_sol) > sol_fees);
+}
@@ -0,0 +1,95 @@
+//! sync and skim after tokens are sent straight to the vaults, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::error::PoolError;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+const DONATED_USDC: u64 = 7 * USDC;
+const DONATED_SOL: u64 = LAMPORTS_PER_SOL / 10;
+
+/// pool with a swap's fees in it and a donation to both vaults, `lp` is its authority
+fn pool_with_donation() -> (TestPool, Keypair) {
+    let mut pool = TestPool::new();
+    let lp = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let trader = pool.new_user(1_000 * USDC, 0);
+
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+    let ix = pool.swap_ix(&trader.pubkey(), 100 * USDC, true);
+    pool.send(ix, &[&trader]).unwrap();
+
+    let (pool_key, usdc_mint, wrapped_sol_mint) = (pool.pool, pool.usdc_mint, pool.wrapped_sol_mint);
+    pool.fund(&pool_key, &usdc_mint, DONATED_USDC);
+    pool.fund(&pool_key, &wrapped_sol_mint, DONATED_SOL);
+
+    (pool, lp)
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn sync_folds_a_donation_into_the_reserves() {
+    let (mut pool, lp) = pool_with_donation();
+    let before = pool.pool_state().unwrap();
+    assert!(before.fees_collected_usdc > 0);
+
+    let ix = pool.sync_ix(&lp.pubkey());
+    pool.send(ix, &[&lp]).unwrap();
+
+    // the fees stay fees, only the reserves take the donation
+    let after = pool.pool_state().unwrap();
+    assert_eq!(after.total_usdc_deposit, before.total_usdc_deposit + DONATED_USDC);
+    assert_eq!(after.total_sol_deposit, before.total_sol_deposit + DONATED_SOL);
+    assert_eq!((after.fees_collected_usdc, after.fees_collected_sol), (before.fees_collected_usdc, before.fees_collected_sol));
+    assert_eq!(pool.token_balance(&pool.pool_usdc_ata), after.total_usdc_deposit + after.fees_collected_usdc);
+    assert_eq!(pool.token_balance(&pool.pool_sol_ata), after.total_sol_deposit + after.fees_collected_sol);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn skim_sends_a_donation_to_the_recipient() {
+    let (mut pool, lp) = pool_with_donation();
+    let recipient = pool.new_user(0, 0);
+    let before = pool.pool_state().unwrap();
+
+    let ix = pool.skim_ix(&lp.pubkey(), &recipient.pubkey());
+    pool.send(ix, &[&lp]).unwrap();
+
+    let recipient_usdc = get_associated_token_address(&recipient.pubkey(), &pool.usdc_mint);
+    let recipient_sol = get_associated_token_address(&recipient.pubkey(), &pool.wrapped_sol_mint);
+    assert_eq!(pool.token_balance(&recipient_usdc), DONATED_USDC);
+    assert_eq!(pool.token_balance(&recipient_sol), DONATED_SOL);
+
+    // the pool's books are untouched, the vaults are back to matching them
+    let after = pool.pool_state().unwrap();
+    assert_eq!((after.total_usdc_deposit, after.total_sol_deposit), (before.total_usdc_deposit, before.total_sol_deposit));
+    assert_eq!(pool.token_balance(&pool.pool_usdc_ata), after.total_usdc_deposit + after.fees_collected_usdc);
+    assert_eq!(pool.token_balance(&pool.pool_sol_ata), after.total_sol_deposit + after.fees_collected_sol);
+
+    // nothing is left over for a second skim
+    let ix = pool.skim_ix(&lp.pubkey(), &recipient.pubkey());
+    pool.send(ix, &[&lp]).unwrap();
+    assert_eq!(pool.token_balance(&recipient_usdc), DONATED_USDC);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn only_the_authority_reconciles() {
+    let (mut pool, _) = pool_with_donation();
+    let stranger = pool.new_user(0, 0);
+    let before = pool.pool_state().unwrap();
+
+    let ix = pool.sync_ix(&stranger.pubkey());
+    assert_program_error(pool.send(ix, &[&stranger]), PoolError::Unauthorized);
+    let ix = pool.skim_ix(&stranger.pubkey(), &stranger.pubkey());
+    assert_program_error(pool.send(ix, &[&stranger]), PoolError::Unauthorized);
+
+    let after = pool.pool_state().unwrap();
+    assert_eq!((after.total_usdc_deposit, after.total_sol_deposit), (before.total_usdc_deposit, before.total_sol_deposit));
+    let stranger_usdc = get_associated_token_address(&stranger.pubkey(), &pool.usdc_mint);
+    assert_eq!(pool.token_balance(&stranger_usdc), 0);
+}
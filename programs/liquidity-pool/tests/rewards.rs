@@ -0,0 +1,52 @@
+//! reward claims against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_lang::error::ErrorCode;
+use common::{assert_program_error, TestPool, FEE_TIER, LAMPORTS_PER_SOL};
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn positions_only_claim_from_their_own_pool() {
+    let mut pool = TestPool::new();
+    let alice = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let bob = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&alice.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&alice]).unwrap();
+    let alice_shares = pool.user_state(&alice.pubkey()).unwrap().total_shares;
+
+    // bob opens and funds rewards on a second pool of the same mints
+    pool.use_fee_tier(5);
+    let ix = pool.deposit_ix(&bob.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&bob]).unwrap();
+
+    let reward_mint = pool.create_mint(6);
+    pool.fund(&bob.pubkey(), &reward_mint, 1_000_000);
+    let now = pool.now();
+    let ix = pool.initialize_rewards_ix(&bob.pubkey(), &reward_mint, 1_000, now, now + 1_000);
+    pool.send(ix, &[&bob]).unwrap();
+    pool.warp(100);
+
+    // alice's shares are in the first pool, they earn nothing here
+    let ix = pool.claim_rewards_ix(&alice.pubkey(), &reward_mint);
+    assert_program_error(pool.send(ix, &[&alice]), ErrorCode::AccountNotInitialized);
+
+    let ix = pool.claim_rewards_ix(&bob.pubkey(), &reward_mint);
+    pool.send(ix, &[&bob]).unwrap();
+    // 100s at 1_000 a second, less the accumulator's rounding
+    let bob_rewards = pool.token_balance(&get_associated_token_address(&bob.pubkey(), &reward_mint));
+    assert!((99_999..=100_000).contains(&bob_rewards), "{bob_rewards}");
+
+    // depositing here opens a second position, the first one keeps its shares
+    let ix = pool.deposit_ix(&alice.pubkey(), 50 * USDC, LAMPORTS_PER_SOL / 2);
+    pool.send(ix, &[&alice]).unwrap();
+    assert_eq!(pool.user_state(&alice.pubkey()).unwrap().pool, pool.pool);
+
+    pool.use_fee_tier(FEE_TIER);
+    assert_eq!(pool.user_state(&alice.pubkey()).unwrap().total_shares, alice_shares);
+}
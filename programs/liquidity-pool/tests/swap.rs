@@ -0,0 +1,56 @@
+//! swaps against the built program, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::{assert_program_error, TestPool, LAMPORTS_PER_SOL};
+use liquidity_pool::{error::PoolError, state::MAX_DYNAMIC_FEE_BPS};
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn swap_fails_below_min_amount_out() {
+    let mut pool = TestPool::new();
+    let lp = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+    let trader = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+
+    let mut state = pool.pool_state().unwrap();
+    let fee_bps = state.swap_fee_bps(pool.now()).unwrap();
+    let (sol_out, _) = state.swap_usdc_for_sol(100 * USDC, fee_bps).unwrap();
+
+    let ix = pool.swap_min_out_ix(&trader.pubkey(), 100 * USDC, sol_out + 1, true);
+    assert_program_error(pool.send(ix, &[&trader]), PoolError::SlippageExceeded);
+
+    let trader_sol = get_associated_token_address(&trader.pubkey(), &pool.wrapped_sol_mint);
+    let before = pool.token_balance(&trader_sol);
+    let ix = pool.swap_min_out_ix(&trader.pubkey(), 100 * USDC, sol_out, true);
+    pool.send(ix, &[&trader]).unwrap();
+    assert_eq!(pool.token_balance(&trader_sol) - before, sol_out);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn dynamic_fee_is_capped() {
+    let mut pool = TestPool::new();
+    let lp = pool.new_user(1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+
+    let ix = pool.deposit_ix(&lp.pubkey(), 500 * USDC, 5 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+
+    // the pool's authority can't, only the config admin sets the bounds
+    let ix = pool.set_dynamic_fee_ix(&lp.pubkey(), true, 30, MAX_DYNAMIC_FEE_BPS);
+    assert_program_error(pool.send(ix, &[&lp]), PoolError::Unauthorized);
+
+    let admin = pool.payer.pubkey();
+    let ix = pool.set_dynamic_fee_ix(&admin, true, 30, MAX_DYNAMIC_FEE_BPS + 1);
+    assert_program_error(pool.send(ix, &[]), PoolError::InvalidFeeBounds);
+
+    let ix = pool.set_dynamic_fee_ix(&admin, true, 30, MAX_DYNAMIC_FEE_BPS);
+    pool.send(ix, &[]).unwrap();
+    assert_eq!(pool.pool_state().unwrap().max_fee_bps, MAX_DYNAMIC_FEE_BPS);
+}
@@ -0,0 +1,120 @@
+//! compute units of process_swap in the current zero-copy build against the `baseline` tag,
+//! which loaded the pool as a borsh `Account<Pool>`. `anchor run bench-swap` builds both and
+//! runs it.
+
+mod common;
+
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program},
+    Discriminator,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address},
+    token::spl_token,
+};
+use common::{TestPool, LAMPORTS_PER_SOL};
+use solana_signer::Signer;
+
+/// the program built from the `baseline` tag
+const BASELINE_SO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/liquidity_pool_baseline.so");
+const USDC: u64 = 1_000_000;
+
+/// instruction data of the baseline build, its deposit and swap only take the amounts
+fn baseline_data(discriminator: &[u8], amounts: &[u64]) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    for amount in amounts {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+    data
+}
+
+/// the baseline pool at [b"pool", usdc, sol] and its vaults
+fn baseline_pool(pool: &TestPool) -> (Pubkey, Pubkey, Pubkey) {
+    let (address, _) =
+        Pubkey::find_program_address(&[b"pool", pool.usdc_mint.as_ref(), pool.wrapped_sol_mint.as_ref()], &liquidity_pool::ID);
+    (address, get_associated_token_address(&address, &pool.usdc_mint), get_associated_token_address(&address, &pool.wrapped_sol_mint))
+}
+
+fn baseline_deposit_ix(pool: &TestPool, user: &Pubkey, usdc_amount: u64, wrapped_sol_amount: u64) -> Instruction {
+    let (pool_pda, pool_usdc_ata, pool_sol_ata) = baseline_pool(pool);
+    let (user_pda, _) = Pubkey::find_program_address(&[b"lp", user.as_ref()], &liquidity_pool::ID);
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pool.usdc_mint, false),
+            AccountMeta::new(pool.wrapped_sol_mint, false),
+            AccountMeta::new(get_associated_token_address(user, &pool.usdc_mint), false),
+            AccountMeta::new(get_associated_token_address(user, &pool.wrapped_sol_mint), false),
+            AccountMeta::new(user_pda, false),
+            AccountMeta::new(pool_pda, false),
+            AccountMeta::new(pool_usdc_ata, false),
+            AccountMeta::new(pool_sol_ata, false),
+            AccountMeta::new_readonly(associated_token::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: baseline_data(liquidity_pool::instruction::Deposit::DISCRIMINATOR, &[usdc_amount, wrapped_sol_amount]),
+    }
+}
+
+fn baseline_swap_ix(pool: &TestPool, user: &Pubkey, swap_amount: u64, usdc_in: bool) -> Instruction {
+    let (pool_pda, pool_usdc_ata, pool_sol_ata) = baseline_pool(pool);
+    // the baseline calls the input side quote and the output side base
+    let (quote_mint, base_mint) = if usdc_in { (pool.usdc_mint, pool.wrapped_sol_mint) } else { (pool.wrapped_sol_mint, pool.usdc_mint) };
+    Instruction {
+        program_id: liquidity_pool::ID,
+        accounts: vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pool.usdc_mint, false),
+            AccountMeta::new(pool.wrapped_sol_mint, false),
+            AccountMeta::new(get_associated_token_address(user, &quote_mint), false),
+            AccountMeta::new(get_associated_token_address(user, &base_mint), false),
+            AccountMeta::new(base_mint, false),
+            AccountMeta::new(pool_pda, false),
+            AccountMeta::new(pool_usdc_ata, false),
+            AccountMeta::new(pool_sol_ata, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(associated_token::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: baseline_data(liquidity_pool::instruction::Swap::DISCRIMINATOR, &[swap_amount]),
+    }
+}
+
+/// (usdc -> sol, sol -> usdc) compute units against a pool seeded with 5m usdc / 100k sol
+fn swap_compute_units(
+    mut pool: TestPool,
+    deposit_ix: impl Fn(&TestPool, &Pubkey, u64, u64) -> Instruction,
+    swap_ix: impl Fn(&TestPool, &Pubkey, u64, bool) -> Instruction,
+) -> (u64, u64) {
+    let lp = pool.new_user(10_000_000 * USDC, 200_000 * LAMPORTS_PER_SOL);
+    let ix = deposit_ix(&pool, &lp.pubkey(), 5_000_000 * USDC, 100_000 * LAMPORTS_PER_SOL);
+    pool.send(ix, &[&lp]).unwrap();
+
+    let trader = pool.new_user(10_000 * USDC, 100 * LAMPORTS_PER_SOL);
+    let ix = swap_ix(&pool, &trader.pubkey(), 500 * USDC, true);
+    let usdc_in = pool.send(ix, &[&trader]).unwrap().compute_units_consumed;
+    let ix = swap_ix(&pool, &trader.pubkey(), LAMPORTS_PER_SOL, false);
+    let sol_in = pool.send(ix, &[&trader]).unwrap().compute_units_consumed;
+
+    (usdc_in, sol_in)
+}
+
+#[test]
+#[ignore = "needs both sbf builds, run with `anchor run bench-swap`"]
+fn zero_copy_swaps_use_less_compute_than_borsh() {
+    // the baseline has no config, its pool is created by the first deposit
+    let borsh = swap_compute_units(TestPool::with_program(BASELINE_SO), baseline_deposit_ix, baseline_swap_ix);
+
+    let zero_copy = swap_compute_units(
+        TestPool::new(),
+        |pool, user, usdc_amount, wrapped_sol_amount| pool.deposit_ix(user, usdc_amount, wrapped_sol_amount),
+        |pool, user, swap_amount, usdc_in| pool.swap_ix(user, swap_amount, usdc_in),
+    );
+
+    println!("swap usdc -> sol: borsh {} CU, zero-copy {} CU", borsh.0, zero_copy.0);
+    println!("swap sol -> usdc: borsh {} CU, zero-copy {} CU", borsh.1, zero_copy.1);
+    assert!(zero_copy.0 < borsh.0 && zero_copy.1 < borsh.1);
+}
@@ -0,0 +1,138 @@
+//! routed swaps through the fee tier pools of one pair, `anchor run test-sbf` runs them
+
+mod common;
+
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_spl::associated_token::get_associated_token_address;
+use common::{assert_program_error, TestPool, FEE_TIER, LAMPORTS_PER_SOL};
+use liquidity_pool::{error::PoolError, state::Pool};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+const USDC: u64 = 1_000_000;
+const TIERS: [u16; 3] = [FEE_TIER, 5, 100];
+
+/// a pool in each of `TIERS` and a trader holding only usdc
+fn pools() -> (TestPool, Keypair) {
+    let mut pool = TestPool::new();
+    let lp = pool.new_user(3_000 * USDC, 30 * LAMPORTS_PER_SOL);
+    for tier in TIERS {
+        pool.use_fee_tier(tier);
+        let ix = pool.deposit_ix(&lp.pubkey(), 1_000 * USDC, 10 * LAMPORTS_PER_SOL);
+        pool.send(ix, &[&lp]).unwrap();
+    }
+    pool.use_fee_tier(FEE_TIER);
+
+    let trader = pool.new_user(1_000 * USDC, 0);
+    (pool, trader)
+}
+
+fn state(pool: &mut TestPool, tier: u16) -> Pool {
+    pool.use_fee_tier(tier);
+    pool.pool_state().unwrap()
+}
+
+/// one hop the way swap_route runs it, usdc input pays the fee out of what arrived
+fn hop(state: &mut Pool, amount: u64, usdc_in: bool, now: i64) -> u64 {
+    let fee_bps = state.swap_fee_bps(now).unwrap();
+    if usdc_in {
+        let curve_amount = ((amount as u128) * 10_000 / (10_000 + fee_bps as u128)) as u64;
+        let (sol_out, fee) = state.swap_usdc_for_sol(curve_amount, fee_bps).unwrap();
+        state.add_fees(amount - curve_amount - fee, 0).unwrap();
+        sol_out
+    } else {
+        state.swap_sol_for_usdc(amount, fee_bps).unwrap().0
+    }
+}
+
+/// usdc -> sol -> usdc -> sol through every tier, with the sol it should end with
+fn three_hop_route(pool: &mut TestPool, amount_in: u64) -> (Vec<Vec<AccountMeta>>, u64) {
+    let now = pool.now();
+    let mut amount = amount_in;
+    let mut hops = Vec::new();
+    for (i, tier) in TIERS.into_iter().enumerate() {
+        let mut state = state(pool, tier);
+        amount = hop(&mut state, amount, i % 2 == 0, now);
+        hops.push(pool.route_hop());
+    }
+    pool.use_fee_tier(FEE_TIER);
+    (hops, amount)
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn routes_through_several_pools() {
+    let (mut pool, trader) = pools();
+    let amount_in = 100 * USDC;
+    let (hops, expected_out) = three_hop_route(&mut pool, amount_in);
+    let reserves: Vec<(u64, u64)> = TIERS
+        .into_iter()
+        .map(|tier| {
+            let state = state(&mut pool, tier);
+            (state.total_usdc_deposit, state.total_sol_deposit)
+        })
+        .collect();
+    pool.use_fee_tier(FEE_TIER);
+
+    let (usdc_mint, wrapped_sol_mint) = (pool.usdc_mint, pool.wrapped_sol_mint);
+    let ix = pool.swap_route_ix(&trader.pubkey(), &usdc_mint, &wrapped_sol_mint, amount_in, expected_out, &hops);
+    pool.send(ix, &[&trader]).unwrap();
+
+    let trader_usdc = get_associated_token_address(&trader.pubkey(), &usdc_mint);
+    let trader_sol = get_associated_token_address(&trader.pubkey(), &wrapped_sol_mint);
+    assert_eq!(pool.token_balance(&trader_usdc), 1_000 * USDC - amount_in);
+    assert_eq!(pool.token_balance(&trader_sol), expected_out);
+
+    // usdc in, sol in, usdc in
+    for (i, (tier, (usdc_before, sol_before))) in TIERS.into_iter().zip(reserves).enumerate() {
+        let state = state(&mut pool, tier);
+        if i % 2 == 0 {
+            assert!(state.total_usdc_deposit > usdc_before && state.total_sol_deposit < sol_before);
+        } else {
+            assert!(state.total_usdc_deposit < usdc_before && state.total_sol_deposit > sol_before);
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn route_output_below_min_amount_out_fails() {
+    let (mut pool, trader) = pools();
+    let (hops, expected_out) = three_hop_route(&mut pool, 100 * USDC);
+    let (usdc_mint, wrapped_sol_mint) = (pool.usdc_mint, pool.wrapped_sol_mint);
+
+    // only the final output counts, the intermediate hops are far above it
+    let ix = pool.swap_route_ix(&trader.pubkey(), &usdc_mint, &wrapped_sol_mint, 100 * USDC, expected_out + 1, &hops);
+    assert_program_error(pool.send(ix, &[&trader]), PoolError::SlippageExceeded);
+
+    let trader_usdc = get_associated_token_address(&trader.pubkey(), &usdc_mint);
+    assert_eq!(pool.token_balance(&trader_usdc), 1_000 * USDC);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn same_pool_twice_is_rejected() {
+    let (mut pool, trader) = pools();
+    let (usdc_mint, wrapped_sol_mint) = (pool.usdc_mint, pool.wrapped_sol_mint);
+    let first = pool.route_hop();
+    pool.use_fee_tier(5);
+    let second = pool.route_hop();
+
+    // usdc -> sol -> usdc back through the first pool
+    let ix = pool.swap_route_ix(&trader.pubkey(), &usdc_mint, &wrapped_sol_mint, 10 * USDC, 0, &[first.clone(), second, first]);
+    assert_program_error(pool.send(ix, &[&trader]), PoolError::InvalidRoute);
+}
+
+#[test]
+#[ignore = "needs the sbf build, run with `anchor run test-sbf`"]
+fn vault_of_another_pool_is_rejected() {
+    let (mut pool, trader) = pools();
+    let (usdc_mint, wrapped_sol_mint) = (pool.usdc_mint, pool.wrapped_sol_mint);
+    let mut hop = pool.route_hop();
+    pool.use_fee_tier(5);
+    // a real usdc vault, but not this pool's
+    hop[3].pubkey = pool.pool_usdc_ata;
+
+    let ix = pool.swap_route_ix(&trader.pubkey(), &usdc_mint, &wrapped_sol_mint, 10 * USDC, 0, &[hop]);
+    assert_program_error(pool.send(ix, &[&trader]), PoolError::InvalidRoute);
+}
@@ -3,6 +3,7 @@ import { Program } from "@coral-xyz/anchor";
 import { LiquidityPool } from "../target/types/liquidity_pool";
 import {mintTo , createMint, createAccount, getOrCreateAssociatedTokenAccount, TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync, getAccount} from "@solana/spl-token";
 import NodeWallet from "@coral-xyz/anchor/dist/cjs/nodewallet";
+import { expect } from "chai";
 
 describe("liquidity-pool", () => {
   // Configure the client to use the local cluster.
@@ -36,6 +37,26 @@ describe("liquidity-pool", () => {
   const userB = anchor.web3.Keypair.generate();
   const swapUser = anchor.web3.Keypair.generate()
 
+  const FEE_TIER = 30
+  const feeTierSeed = Buffer.alloc(2)
+  feeTierSeed.writeUInt16LE(FEE_TIER)
+
+  const U64_MAX = new anchor.BN("18446744073709551615")
+
+  async function expectError(tx: Promise<unknown>, code: string) {
+    try {
+      await tx
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal(code)
+      return
+    }
+    expect.fail(`expected ${code}`)
+  }
+
+  async function balance(ata: anchor.web3.PublicKey) {
+    return Number((await getAccount(provider.connection, ata)).amount)
+  }
+
   it("airdrop some sol to the user wallet" ,async () => {
     const userAtx = await provider.connection.requestAirdrop(
       userA.publicKey, 
@@ -122,7 +143,7 @@ describe("liquidity-pool", () => {
     // console.log("this is the user b ", userBSolAta.toBase58())
 
     let [pda_key, bump] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("pool"), usdc_mint.toBuffer(), wrapped_solana_mint.toBuffer()],
+      [Buffer.from("pool"), usdc_mint.toBuffer(), wrapped_solana_mint.toBuffer(), feeTierSeed],
       program.programId
     )
 
@@ -202,12 +223,12 @@ describe("liquidity-pool", () => {
   it("get pda address for the liquidity provider", async () => {
 
     [userAPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("lp"), userA.publicKey.toBuffer()], 
+      [Buffer.from("lp"), pool_pda.toBuffer(), userA.publicKey.toBuffer()], 
       program.programId
     ) 
 
     let a = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("lp"), userB.publicKey.toBuffer()],
+      [Buffer.from("lp"), pool_pda.toBuffer(), userB.publicKey.toBuffer()],
       program.programId
     )
     userBPda = a[0]
@@ -216,12 +237,29 @@ describe("liquidity-pool", () => {
 
   })
 
+  it("initialise the global config with the default fee tiers", async () => {
+    const tx = await program.methods.initializeConfig()
+    .accounts({
+      admin: wallet.publicKey
+    })
+    .rpc()
+
+    const [configPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    )
+    const config = await program.account.config.fetch(configPda)
+
+    expect(config.admin.toBase58()).to.equal(wallet.publicKey.toBase58())
+    expect(config.feeTiers).to.include(FEE_TIER)
+  })
+
   it("Is initialized pool!", async () => {
     // Add your test here.
     let usdc_amount = 500_000_0 * USDC_DECIMALS;
     let sol_amount = 10_000_0 * LAMPORTS_PER_SOL
 
-    const tx = await program.methods.deposit(new anchor.BN(usdc_amount),new anchor.BN(sol_amount))
+    const tx = await program.methods.deposit(new anchor.BN(usdc_amount),new anchor.BN(sol_amount), FEE_TIER, false)
     .accountsPartial({
       signer: userA.publicKey, 
       usdcMint: usdc_mint, 
@@ -232,25 +270,28 @@ describe("liquidity-pool", () => {
       poolPda: pool_pda, 
       poolSolAta: poolSolAta, 
       poolUsdcAta: poolUsdcAta,
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userA])
     .rpc();
-    console.log("Your transaction signature", tx);
-
-    const poolusdcAccountInfo = await getAccount(provider.connection, poolUsdcAta)
-    const poolSolAtaInfo = await getAccount(provider.connection,poolSolAta)
 
+    expect(await balance(poolUsdcAta)).to.equal(usdc_amount)
+    expect(await balance(poolSolAta)).to.equal(sol_amount)
 
-    console.log("This is the balance  of the liquidity pool usdc after swapping", Number(poolusdcAccountInfo.amount) / USDC_DECIMALS)
-    console.log("This is the balance of the liquidity pool solana after swapping", Number(poolSolAtaInfo.amount) / LAMPORTS_PER_SOL)
+    const poolPdaData = await program.account.pool.fetch(pool_pda)
+    expect(poolPdaData.authority.toBase58()).to.equal(userA.publicKey.toBase58())
+    expect(poolPdaData.totalUsdcDeposit.toNumber()).to.equal(usdc_amount)
+    expect(poolPdaData.totalSolDeposit.toNumber()).to.equal(sol_amount)
   });
 
   it("userB try to provide liqiuidity", async () => {
     const usdcAmount = 100_000_0 * USDC_DECIMALS;
     const solAmount = 200_00 * LAMPORTS_PER_SOL;
+    const usdcBefore = await balance(poolUsdcAta)
+    const solBefore = await balance(poolSolAta)
 
-    const tx = await program.methods.deposit(new anchor.BN(usdcAmount),new anchor.BN(solAmount))
+    const tx = await program.methods.deposit(new anchor.BN(usdcAmount),new anchor.BN(solAmount), FEE_TIER, false)
     .accountsPartial({
       signer: userB.publicKey, 
       usdcMint: usdc_mint, 
@@ -261,22 +302,24 @@ describe("liquidity-pool", () => {
       poolPda: pool_pda, 
       poolSolAta: poolSolAta, 
       poolUsdcAta: poolUsdcAta,
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userB])
     .rpc()
-    const poolusdcAccountInfo = await getAccount(provider.connection, poolUsdcAta)
-    const poolSolAtaInfo = await getAccount(provider.connection,poolSolAta)
-
 
-    console.log("This is the balance  of the liquidity pool usdc after swapping", Number(poolusdcAccountInfo.amount) / USDC_DECIMALS)
-    console.log("This is the balance of the liquidity pool solana after swapping", Number(poolSolAtaInfo.amount) / LAMPORTS_PER_SOL)
+    // the deposit matches the pool ratio exactly, so all of it goes in
+    expect(await balance(poolUsdcAta)).to.equal(usdcBefore + usdcAmount)
+    expect(await balance(poolSolAta)).to.equal(solBefore + solAmount)
+    const userBData = await program.account.user.fetch(userBPda)
+    expect(userBData.totalShares.toNumber()).to.be.greaterThan(0)
   })
   it("userB try to provide liqiuidity with 1 percent change", async () => {
     const usdcAmount = 101 * USDC_DECIMALS;
     const solAmount = 2 * LAMPORTS_PER_SOL;
+    const sharesBefore = (await program.account.user.fetch(userBPda)).totalShares
 
-    const tx = await program.methods.deposit(new anchor.BN(usdcAmount),new anchor.BN(solAmount))
+    const tx = await program.methods.deposit(new anchor.BN(usdcAmount),new anchor.BN(solAmount), FEE_TIER, false)
     .accountsPartial({
       signer: userB.publicKey, 
       usdcMint: usdc_mint, 
@@ -287,18 +330,22 @@ describe("liquidity-pool", () => {
       poolPda: pool_pda, 
       poolSolAta: poolSolAta, 
       poolUsdcAta: poolUsdcAta,
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userB])
     .rpc()
+
+    const userBData = await program.account.user.fetch(userBPda)
+    expect(userBData.totalShares.gt(sharesBefore)).to.be.true
   })
 
    it("userB try to provide liqiuidity with  imabalance liquidity", async () => {
-    //this test should fail 
+    // 2% off the pool ratio, deposits only accept 1%
     const usdcAmount = 102 * USDC_DECIMALS;
     const solAmount = 2 * LAMPORTS_PER_SOL;
 
-    const tx = await program.methods.deposit(new anchor.BN(usdcAmount),new anchor.BN(solAmount))
+    await expectError(program.methods.deposit(new anchor.BN(usdcAmount),new anchor.BN(solAmount), FEE_TIER, false)
     .accountsPartial({
       signer: userB.publicKey, 
       usdcMint: usdc_mint, 
@@ -309,16 +356,85 @@ describe("liquidity-pool", () => {
       poolPda: pool_pda, 
       poolSolAta: poolSolAta, 
       poolUsdcAta: poolUsdcAta,
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userB])
+    .rpc(), "ImbalancedDeposit")
+  })
+
+  it("new pools are created on the current version", async () => {
+    const poolPdaData = await program.account.pool.fetch(pool_pda)
+    expect(poolPdaData.version).to.equal(1)
+  })
+
+  it("pool authority can't set the dynamic fee", async () => {
+    await expectError(program.methods.setDynamicFee(true, new anchor.BN(10), new anchor.BN(100))
+    .accountsPartial({
+      admin: userA.publicKey,
+      usdcMint: usdc_mint,
+      wrappedSolMint: wrapped_solana_mint,
+      poolPda: pool_pda
+    })
+    .signers([userA])
+    .rpc(), "Unauthorized")
+  })
+
+  it("dynamic fee bounds above the cap are rejected", async () => {
+    await expectError(program.methods.setDynamicFee(true, new anchor.BN(10), new anchor.BN(1_001))
+    .accountsPartial({
+      admin: wallet.publicKey,
+      usdcMint: usdc_mint,
+      wrappedSolMint: wrapped_solana_mint,
+      poolPda: pool_pda
+    })
+    .rpc(), "InvalidFeeBounds")
+  })
+
+  it("config admin enables dynamic fee", async () => {
+    const tx = await program.methods.setDynamicFee(true, new anchor.BN(10), new anchor.BN(100))
+    .accountsPartial({
+      admin: wallet.publicKey,
+      usdcMint: usdc_mint,
+      wrappedSolMint: wrapped_solana_mint,
+      poolPda: pool_pda
+    })
     .rpc()
+
+    const poolPdaData = await program.account.pool.fetch(pool_pda)
+
+    expect(poolPdaData.dynamicFeeEnabled).to.equal(1)
+    expect(poolPdaData.minFeeBps.toNumber()).to.equal(10)
+    expect(poolPdaData.maxFeeBps.toNumber()).to.equal(100)
+  })
+
+  it("swap fails when the output is below min_amount_out", async () => {
+    await expectError(program.methods.swap(new anchor.BN(500 * USDC_DECIMALS), U64_MAX, false)
+    .accountsPartial({
+      signer: swapUser.publicKey, 
+      usdcMint: usdc_mint, 
+      wrappedSolMint: wrapped_solana_mint, 
+      baseMint: wrapped_solana_mint, 
+      poolUsdcAta: poolUsdcAta, 
+      poolSolAta: poolSolAta, 
+      userBaseAta: swapUserSolAta, 
+      userQuoteAta: swapUserUsdcAta,
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID,
+      baseTokenProgram: TOKEN_PROGRAM_ID
+    })
+    .signers([swapUser])
+    .rpc(), "SlippageExceeded")
   })
 
   it("swaping the usdc token with solana from the pool", async () => {
     const swapAmount = 500 * USDC_DECIMALS
+    const poolUsdcBefore = await balance(poolUsdcAta)
+    const poolSolBefore = await balance(poolSolAta)
+    const userSolBefore = await balance(swapUserSolAta)
+    const feesBefore = (await program.account.pool.fetch(pool_pda)).feesCollectedUsdc
 
-    const tx = await program.methods.swap(new anchor.BN(swapAmount))
+    const tx = await program.methods.swap(new anchor.BN(swapAmount), new anchor.BN(1), false)
     .accountsPartial({
       signer: swapUser.publicKey, 
       usdcMint: usdc_mint, 
@@ -328,25 +444,31 @@ describe("liquidity-pool", () => {
       poolSolAta: poolSolAta, 
       userBaseAta: swapUserSolAta, 
       userQuoteAta: swapUserUsdcAta,
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID,
+      baseTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([swapUser])
     .rpc()
 
-    const poolusdcAccountInfo = await getAccount(provider.connection, poolUsdcAta)
-    const poolSolAtaInfo = await getAccount(provider.connection,poolSolAta)
     const poolPdaData = await program.account.pool.fetch(pool_pda)
-
-    console.log("this is the data of the account after swapping", poolPdaData.feesCollectedUsdc.toNumber() / USDC_DECIMALS)
-
-    console.log("This is the balance  of the liquidity pool usdc after swapping", Number(poolusdcAccountInfo.amount) / USDC_DECIMALS)
-    console.log("This is the balance of the liquidity pool solana after swapping", Number(poolSolAtaInfo.amount) / LAMPORTS_PER_SOL)
+    const solOut = poolSolBefore - await balance(poolSolAta)
+
+    // usdc swaps pay the fee on top of the swap amount
+    const fee = poolPdaData.feesCollectedUsdc.sub(feesBefore).toNumber()
+    expect(fee).to.be.greaterThan(0)
+    expect(await balance(poolUsdcAta)).to.equal(poolUsdcBefore + swapAmount + fee)
+    expect(solOut).to.be.greaterThan(0)
+    expect(await balance(swapUserSolAta)).to.equal(userSolBefore + solOut)
   })
 
   it("swaping the solana token for usdc token from the pool", async () => {
     const swapAmount = 500 * LAMPORTS_PER_SOL
+    const poolUsdcBefore = await balance(poolUsdcAta)
+    const poolSolBefore = await balance(poolSolAta)
+    const userUsdcBefore = await balance(swapUserUsdcAta)
 
-    const tx = await program.methods.swap(new anchor.BN(swapAmount))
+    const tx = await program.methods.swap(new anchor.BN(swapAmount), new anchor.BN(1), false)
     .accountsPartial({
       signer: swapUser.publicKey, 
       usdcMint: usdc_mint, 
@@ -356,24 +478,64 @@ describe("liquidity-pool", () => {
       poolSolAta: poolSolAta, 
       userBaseAta: swapUserUsdcAta, 
       userQuoteAta: swapUserSolAta,
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID,
+      baseTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([swapUser])
     .rpc()
 
-    const poolusdcAccountInfo = await getAccount(provider.connection, poolUsdcAta)
-    const poolSolAtaInfo = await getAccount(provider.connection,poolSolAta)
-    const poolPdaData = await program.account.pool.fetch(pool_pda)
+    const usdcOut = poolUsdcBefore - await balance(poolUsdcAta)
 
-    console.log("this is the data of the account after swapping", poolPdaData.feesCollectedUsdc.toNumber() / USDC_DECIMALS)
+    expect(await balance(poolSolAta)).to.equal(poolSolBefore + swapAmount)
+    expect(usdcOut).to.be.greaterThan(0)
+    expect(await balance(swapUserUsdcAta)).to.equal(userUsdcBefore + usdcOut)
+  })
 
-    console.log("This is the balance  of the liquidity pool usdc after swapping", Number(poolusdcAccountInfo.amount) / USDC_DECIMALS)
-    console.log("This is the balance of the liquidity pool solana after swapping", Number(poolSolAtaInfo.amount) / LAMPORTS_PER_SOL)
-    
+  it("flash borrow and repay in the same transaction", async () => {
+    const borrowAmount = 1_000 * USDC_DECIMALS
+    const accounts = {
+      signer: swapUser.publicKey,
+      usdcMint: usdc_mint,
+      wrappedSolMint: wrapped_solana_mint,
+      userUsdcAta: swapUserUsdcAta,
+      userSolAta: swapUserSolAta,
+      poolPda: pool_pda,
+      poolUsdcAta: poolUsdcAta,
+      poolSolAta: poolSolAta,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
+    }
+
+    const borrowIx = await program.methods.flashBorrow(new anchor.BN(borrowAmount), new anchor.BN(0))
+    .accountsPartial(accounts)
+    .instruction()
+    const repayIx = await program.methods.flashRepay()
+    .accountsPartial(accounts)
+    .instruction()
+
+    const feesBefore = (await program.account.pool.fetch(pool_pda)).feesCollectedUsdc
+    const poolUsdcBefore = await balance(poolUsdcAta)
+
+    const tx = new anchor.web3.Transaction().add(borrowIx, repayIx)
+    await provider.sendAndConfirm(tx, [swapUser])
+
+    const poolPdaData = await program.account.pool.fetch(pool_pda)
+    const fee = poolPdaData.feesCollectedUsdc.sub(feesBefore).toNumber()
+
+    expect(poolPdaData.flashLoanActive).to.equal(0)
+    expect(fee).to.be.greaterThan(0)
+    expect(await balance(poolUsdcAta)).to.equal(poolUsdcBefore + fee)
   })
 
   it("userB withdraw amount", async () => {
-    const tx = await program.methods.withdraw()
+    const userBUsdcBefore = await balance(userBUsdcAta)
+    const userBSolBefore = await balance(userBSolAta)
+    const poolUsdcBefore = await balance(poolUsdcAta)
+    const poolSolBefore = await balance(poolSolAta)
+
+    const tx = await program.methods.withdraw(false)
     .accountsPartial({
       signer: userB.publicKey, 
       usdcMint: usdc_mint, 
@@ -384,24 +546,24 @@ describe("liquidity-pool", () => {
       poolWrappedSolAta: poolSolAta,
       userSolAta: userBSolAta, 
       userUsdcAta: userBUsdcAta, 
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userB])
     .rpc()
 
-    const poolusdcAccountInfo = await getAccount(provider.connection, poolUsdcAta)
-    const poolSolAtaInfo = await getAccount(provider.connection,poolSolAta)
-    const poolPdaData = await program.account.pool.fetch(pool_pda)
-
-    console.log("this is the data of the account after withdrawing", poolPdaData.feesCollectedUsdc.toNumber() / USDC_DECIMALS)
-
-
-    console.log("This is the balance  of the liquidity pool usdc after swapping", Number(poolusdcAccountInfo.amount) / USDC_DECIMALS)
-    console.log("This is the balance of the liquidity pool solana after swapping", Number(poolSolAtaInfo.amount) / LAMPORTS_PER_SOL)
+    // whatever left the vaults went to userB and the position is closed
+    const usdcOut = poolUsdcBefore - await balance(poolUsdcAta)
+    const solOut = poolSolBefore - await balance(poolSolAta)
+    expect(usdcOut).to.be.greaterThan(0)
+    expect(solOut).to.be.greaterThan(0)
+    expect(await balance(userBUsdcAta)).to.equal(userBUsdcBefore + usdcOut)
+    expect(await balance(userBSolAta)).to.equal(userBSolBefore + solOut)
+    expect(await provider.connection.getAccountInfo(userBPda)).to.be.null
   })
 
-  it("userB withdraw amount", async () => {
-    const tx = await program.methods.withdraw()
+  it("userB can't withdraw a closed position", async () => {
+    await expectError(program.methods.withdraw(false)
     .accountsPartial({
       signer: userB.publicKey, 
       usdcMint: usdc_mint, 
@@ -412,14 +574,15 @@ describe("liquidity-pool", () => {
       poolWrappedSolAta: poolSolAta,
       userSolAta: userBSolAta, 
       userUsdcAta: userBUsdcAta, 
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userB])
-    .rpc()
+    .rpc(), "AccountNotInitialized")
   })
 
   it("userA withdraw amount", async () => {
-    const tx = await program.methods.withdraw()
+    const tx = await program.methods.withdraw(false)
     .accountsPartial({
       signer: userA.publicKey,
       usdcMint: usdc_mint, 
@@ -430,20 +593,16 @@ describe("liquidity-pool", () => {
       poolWrappedSolAta: poolSolAta,
       userSolAta: userASolAta, 
       userUsdcAta: userAUsdcAta, 
-      tokenProgram: TOKEN_PROGRAM_ID
+      usdcTokenProgram: TOKEN_PROGRAM_ID,
+      solTokenProgram: TOKEN_PROGRAM_ID
     })
     .signers([userA])
     .rpc()
 
-    const poolusdcAccountInfo = await getAccount(provider.connection, poolUsdcAta)
-    const poolSolAtaInfo = await getAccount(provider.connection,poolSolAta)
+    // userA was the last provider
     const poolPdaData = await program.account.pool.fetch(pool_pda)
-
-    console.log("this is the data of the account usdc", poolPdaData.totalUsdcDeposit.toNumber() / USDC_DECIMALS)
-
-    console.log("This is the balance of the liquidity pool usdc", Number(poolusdcAccountInfo.amount) / USDC_DECIMALS)
-    console.log("This is the balance of the liquidity pool solana", Number(poolSolAtaInfo.amount) / LAMPORTS_PER_SOL)
-
+    expect(poolPdaData.totalShares.toNumber()).to.equal(0)
+    expect(await provider.connection.getAccountInfo(userAPda)).to.be.null
   })
 
 });